@@ -6,314 +6,2588 @@ pub struct Ticket {
     level: i8,
     #[mutable]
     last_throw: String,
+    #[mutable]
+    recent_throws: Vec<String>,
+    #[mutable]
+    rounds_played: u32,
+    #[mutable]
+    wins: u16,
+    #[mutable]
+    losses: u16,
+    #[mutable]
+    last_played_epoch: u64,
+    #[mutable]
+    minted_epoch: u64,
+    #[mutable]
+    nickname: String,
+    #[mutable]
+    win_streak: u8,
+}
+
+// Emitted for every round played, so game history can be rebuilt from the ledger
+#[derive(ScryptoSbor, ScryptoEvent)]
+pub struct RoundPlayed {
+    pub ticket_id: NonFungibleLocalId,
+    pub house_die: i8,
+    pub player_die: i8,
+    pub old_level: i8,
+    pub new_level: i8,
+}
+
+// Emitted when a new ticket is minted
+#[derive(ScryptoSbor, ScryptoEvent)]
+pub struct TicketMinted {
+    pub ticket_id: NonFungibleLocalId,
+}
+
+// Emitted when a ticket is bought, with the price paid for it
+#[derive(ScryptoSbor, ScryptoEvent)]
+pub struct TicketPurchased {
+    pub ticket_id: NonFungibleLocalId,
+    pub price_paid: Decimal,
+}
+
+// Emitted when a ticket is burned
+#[derive(ScryptoSbor, ScryptoEvent)]
+pub struct TicketBurned {
+    pub ticket_id: NonFungibleLocalId,
+    pub level: i8,
+}
+
+// Emitted when a level 0 ticket is reinitialized
+#[derive(ScryptoSbor, ScryptoEvent)]
+pub struct TicketReinitialized {
+    pub ticket_id: NonFungibleLocalId,
+}
+
+// Emitted when an admin sets the level of a ticket, as audit trail
+#[derive(ScryptoSbor, ScryptoEvent)]
+pub struct LevelAdjusted {
+    pub ticket_id: NonFungibleLocalId,
+    pub new_level: i8,
+}
+
+// Emitted when a prize can't be paid, so the operator knows the vault needs topping up
+#[derive(ScryptoSbor, ScryptoEvent)]
+pub struct PrizeUnderfunded {
+    pub ticket_id: NonFungibleLocalId,
+    pub required: Decimal,
+    pub available: Decimal,
+}
+
+// Emitted whenever the XRD in the main wallet changes, reason is one of "deposit", "fund_house",
+// "ticket_sale", "reinit", "stake", "withdrawal", "sweep", "dead_ticket_refund",
+// "consolation", "instant" or "shutdown"
+#[derive(ScryptoSbor, ScryptoEvent)]
+pub struct VaultChanged {
+    pub new_balance: Decimal,
+    pub reason: String,
+}
+
+// Emitted when a ticket drops to level 0, consolation is the XRD set aside for it (0 when unaffordable)
+#[derive(ScryptoSbor, ScryptoEvent)]
+pub struct Busted {
+    pub ticket_id: NonFungibleLocalId,
+    pub consolation: Decimal,
+}
+
+// Emitted when withdraw_to takes XRD out of the main wallet, as audit trail of the withdrawn amount
+#[derive(ScryptoSbor, ScryptoEvent)]
+pub struct FundsWithdrawn {
+    pub amount: Decimal,
+}
+
+// Emitted when XRD is swept out of the main wallet for an external buyback
+#[derive(ScryptoSbor, ScryptoEvent)]
+pub struct SweepPerformed {
+    pub amount: Decimal,
+}
+
+// Emitted for every instant round of a fixed-odds game, with the stake and the payout for it
+#[derive(ScryptoSbor, ScryptoEvent)]
+pub struct InstantPlayed {
+    pub house_die: i8,
+    pub player_die: i8,
+    pub stake: Decimal,
+    pub payout: Decimal,
+}
+
+// Emitted when a prize is redeemed, net is paid to the player and the fee is kept for the operator.
+// doubled is true when the prize was doubled by a double prize promo
+#[derive(ScryptoSbor, ScryptoEvent)]
+pub struct PrizeRedeemed {
+    pub ticket_id: NonFungibleLocalId,
+    pub gross: Decimal,
+    pub fee: Decimal,
+    pub net: Decimal,
+    pub doubled: bool,
+}
+
+// Emitted when the admin starts a new leaderboard season, season is the number of the new season
+#[derive(ScryptoSbor, ScryptoEvent)]
+pub struct SeasonReset {
+    pub season: u32,
+}
+
+// Emitted when the admin changes the prize amount, so players chasing the prize are warned
+#[derive(ScryptoSbor, ScryptoEvent)]
+pub struct PrizeAmountChanged {
+    pub old: Decimal,
+    pub new: Decimal,
+}
+
+// Emitted when the admin forces the dice with set_rng_override or lifts it again with None,
+// so players can see when the dice weren't random
+#[derive(ScryptoSbor, ScryptoEvent)]
+pub struct RngOverrideSet {
+    pub rng_override: Option<(i8, i8)>,
+}
+
+// Emitted for every thrown round with the random numbers the dice were drawn from, in the order they
+// were used, see throw_from_raws. Every die draws its own uuids, so the dice are independent.
+// Anyone can recompute the dice from raws, but raws are public: Runtime::generate_uuid is derived from
+// the transaction hash, so this proves the mapping to the dice, not that raws were unpredictable.
+#[derive(ScryptoSbor, ScryptoEvent)]
+pub struct DiceDrawn {
+    pub raws: Vec<u128>,
+    pub house_die: i8,
+    pub player_die: i8,
+}
+
+// Configuration and live state of a component in one struct, returned by snapshot
+#[derive(ScryptoSbor, Debug, PartialEq)]
+pub struct GameSnapshot {
+    pub vault_balance: Decimal,
+    pub prize_vault_balance: Decimal,
+    pub jackpot_balance: Decimal,
+    pub tickets_minted: u64,
+    pub tickets_at_max: u64,
+    pub paused: bool,
+    pub ticket_price: Decimal,
+    pub prize_amount: Decimal,
+    pub start_level: i8,
+    pub win_level: i8,
+    pub dice_mode: u8,
+    pub dice_faces: u8,
+    pub single_die_mode: bool,
+    pub max_tickets: u64,
+}
+
+// Maximum number of re-rolls when house and player throw the same value
+pub const MAX_REROLLS: u8 = 10;
+
+// Maximum number of characters of a ticket nickname
+pub const MAX_NICKNAME_LENGTH: usize = 32;
+
+// Number of throws kept in the play history of a ticket
+pub const RECENT_THROWS: usize = 5;
+
+// Number of throws of all tickets kept for the spectator feed
+pub const RECENT_GLOBAL_THROWS: usize = 20;
+
+// Highest multiplier a player can stake on a round
+pub const MAX_STAKE_MULTIPLIER: u8 = 3;
+
+// Maximum number of tickets playing a team round together
+pub const MAX_TEAM_SIZE: usize = 5;
+
+// Maximum number of random numbers used for a single die roll, see roll_die
+pub const MAX_ROLL_ATTEMPTS: u8 = 64;
+
+// Smallest and largest number of faces a die can have
+pub const MIN_DICE_FACES: u8 = 2;
+pub const MAX_DICE_FACES: u8 = 20;
+
+/*
+    Dice modes, selected when the component is instantiated
+    fair: house and player roll a fair die, every face of a d6 has a 1/6 chance.
+    player favored: the player rolls a loaded die, on a d6 faces 1-5 have a 1/7 chance and face 6 a 2/7 chance.
+        With ties rolled again the player wins 4/7 (57.1%) of the rounds.
+    house favored: the house rolls the loaded die, the house wins 4/7 (57.1%) of the rounds.
+*/
+pub const DICE_MODE_FAIR: u8 = 0;
+pub const DICE_MODE_PLAYER_FAVORED: u8 = 1;
+pub const DICE_MODE_HOUSE_FAVORED: u8 = 2;
+
+/*
+    Strategies to turn a random number into a die roll, switchable at runtime
+    rejection: roll_die, every face equally likely, the default.
+    modulo: roll_die_modulo, a single random number per roll, the low faces are very slightly favoured.
+*/
+pub const RNG_STRATEGY_REJECTION: u8 = 0;
+pub const RNG_STRATEGY_MODULO: u8 = 1;
+
+// Lowest ticket price accepted by instantiate and set_ticket_price, guards against mistyped prices
+pub fn min_ticket_price() -> Decimal {
+    dec!("0.01")
+}
+
+// Highest ticket price accepted by instantiate and set_ticket_price
+pub fn max_ticket_price() -> Decimal {
+    dec!("1000000")
+}
+
+/*
+    Throw the house die and the player die, (house, player) is returned.
+    roll is called with true when the die to roll is loaded in the given dice mode.
+    When both dice are equal they are rolled again, so a tie never favours the house or player.
+    After MAX_REROLLS ties the last throw is kept, which leaves the ticket level unchanged.
+*/
+pub fn throw_dice<F: FnMut(bool) -> i8>(mut roll: F, dice_mode: u8) -> (i8, i8) {
+    let house_loaded = dice_mode == DICE_MODE_HOUSE_FAVORED;
+    let player_loaded = dice_mode == DICE_MODE_PLAYER_FAVORED;
+
+    let mut house_die = roll(house_loaded);
+    let mut player_die = roll(player_loaded);
+    let mut rerolls: u8 = 0;
+    while house_die == player_die && rerolls < MAX_REROLLS {
+        house_die = roll(house_loaded);
+        player_die = roll(player_loaded);
+        rerolls += 1;
+    }
+    (house_die, player_die)
+}
+
+/*
+    Roll a die with the given number of faces using the random numbers supplied by random_source.
+    Taking a 128 bit random number modulo the faces makes the low faces slightly more likely,
+    so instead just enough bits for the faces (plus the extra value of a loaded die) are taken
+    at a time and values above the faces are thrown away (rejection sampling).
+    A loaded die also counts the value equal to faces as its highest face, 
+    so on a d6 face 6 has a 2/7 chance and the others 1/7.
+    The modulo (%) function is pratically a division, this routine is also "cheaper" in network execution
+    At most MAX_ROLL_ATTEMPTS random numbers are used. When all their bits are rejected, which a working
+    random source practically never does, the last random number modulo the faces is the fallback,
+    so the cost of a roll is bounded.
+*/
+pub fn roll_die<F: FnMut() -> u128>(mut random_source: F, faces: u8, loaded: bool) -> i8 {
+    let faces = faces as u128;
+    // number of bits needed to hold the values 0..=faces, 3 bits for a d6
+    let bits = 128 - faces.leading_zeros();
+    let mask = (1u128 << bits) - 1;
+    let mut random: u128 = 0;
+    for _ in 0..MAX_ROLL_ATTEMPTS {
+        random = random_source();
+        let mut bits_left = random;
+        for _ in 0..(128 / bits) {
+            let myval = bits_left & mask;
+            // values from faces upwards are thrown away, redo check on new bits.
+            if myval < faces{ 
+                return (myval+1) as i8 ;
+            }
+            // a loaded die keeps the value faces as an extra highest face
+            if loaded && myval == faces{
+                return faces as i8;
+            }
+            bits_left >>= bits;
+        }
+    }
+    // deterministic fallback, slightly biased but guaranteed to terminate
+    ((random % faces) + 1) as i8
+}
+
+/*
+    Roll a die with the given number of faces from a single random number modulo the faces.
+    A loaded die takes the random number modulo faces + 1 and counts the extra value as its highest face.
+*/
+pub fn roll_die_modulo<F: FnMut() -> u128>(mut random_source: F, faces: u8, loaded: bool) -> i8 {
+    let values = if loaded { faces as u128 + 1 } else { faces as u128 };
+    let die = (random_source() % values) + 1;
+    die.min(faces as u128) as i8
+}
+
+/*
+    Level step of a round in single die mode, the die is thrown against a fixed threshold
+    of half the faces: a roll above the threshold goes up one level, otherwise one level down.
+    On a d6 a 4, 5 or 6 goes up and a 1, 2 or 3 goes down.
+*/
+pub fn single_die_step(roll: i8, dice_faces: u8) -> i8 {
+    if roll > single_die_threshold(dice_faces) { 1 } else { -1 }
+}
+
+/*
+    Roll a die with the given RNG strategy, see RNG_STRATEGY_REJECTION and RNG_STRATEGY_MODULO
+*/
+pub fn roll_die_with_strategy<F: FnMut() -> u128>(random_source: F, faces: u8, loaded: bool, 
+    rng_strategy: u8) -> i8 {
+    match rng_strategy {
+        RNG_STRATEGY_MODULO => roll_die_modulo(random_source, faces, loaded),
+        _ => roll_die(random_source, faces, loaded),
+    }
+}
+
+/*
+    Throw the (house, player) dice of a round with the random numbers supplied by random_source.
+    In single die mode only the player die is rolled, the house die is the threshold to beat.
+*/
+pub fn throw_with_source<F: FnMut() -> u128>(mut random_source: F, dice_faces: u8, dice_mode: u8, 
+    single_die_mode: bool, rng_strategy: u8) -> (i8, i8) {
+    let mut roll = |loaded: bool| {
+        roll_die_with_strategy(&mut random_source, dice_faces, loaded, rng_strategy)
+    };
+    if single_die_mode {
+        return (single_die_threshold(dice_faces), roll(false));
+    }
+    throw_dice(roll, dice_mode)
+}
+
+/*
+    Throw the (house, player) dice of a round again from the raw random numbers of a DiceDrawn event,
+    so off-chain tools can verify the dice. Panics when the raws run out before the dice are thrown.
+*/
+pub fn throw_from_raws(raws: &[u128], dice_faces: u8, dice_mode: u8, single_die_mode: bool, 
+    rng_strategy: u8) -> (i8, i8) {
+    let mut raws = raws.iter();
+    throw_with_source(|| *raws.next().expect("Not enough raw random numbers to throw the dice"), 
+        dice_faces, dice_mode, single_die_mode, rng_strategy)
+}
+
+// Threshold the single die has to beat, 3 for a d6
+pub fn single_die_threshold(dice_faces: u8) -> i8 {
+    (dice_faces / 2) as i8
+}
+
+/*
+    Expected level change of an unstaked round for the given dice, a negative value is the house edge.
+    Each face is weighted 1, the highest face of a loaded die 2. Ties are rolled again up to
+    MAX_REROLLS times and the last tie is kept, so the expected diff of one throw is scaled by
+    1 + t + t^2 + ... + t^MAX_REROLLS with t the chance of a tie.
+    Computed from the configuration only, the result is rounded to two decimals.
+*/
+pub fn expected_delta(dice_faces: u8, dice_mode: u8) -> Decimal {
+    let house = dice_weights(dice_faces, dice_mode == DICE_MODE_HOUSE_FAVORED);
+    let player = dice_weights(dice_faces, dice_mode == DICE_MODE_PLAYER_FAVORED);
+
+    let mut total_diff: i64 = 0;
+    let mut total_ties: i64 = 0;
+    for (h, house_weight) in house.iter().enumerate() {
+        for (p, player_weight) in player.iter().enumerate() {
+            total_diff += house_weight * player_weight * (p as i64 - h as i64);
+            if h == p {
+                total_ties += house_weight * player_weight;
+            }
+        }
+    }
+    let total = Decimal::from(house.iter().sum::<i64>() * player.iter().sum::<i64>());
+    let tie_chance = Decimal::from(total_ties) / total;
+
+    let mut scale = dec!("1");
+    let mut tie_power = dec!("1");
+    for _ in 0..MAX_REROLLS {
+        tie_power = tie_power * tie_chance;
+        scale = scale + tie_power;
+    }
+    (Decimal::from(total_diff) / total * scale)
+        .round(2, RoundingMode::TowardsNearestAndHalfAwayFromZero)
+}
+
+// Weight of each face of a die, 1 for every face and 2 for the highest face of a loaded die
+fn dice_weights(dice_faces: u8, loaded: bool) -> Vec<i64> {
+    let mut weights = vec![1i64; dice_faces as usize];
+    if loaded {
+        weights[dice_faces as usize - 1] = 2;
+    }
+    weights
+}
+
+/*
+    Chance of every level change of an unstaked round as (delta, chance), weighted like expected_delta.
+    Ties are rolled again up to MAX_REROLLS times, a tie after the last reroll keeps the level (delta 0).
+    In single die mode a round goes one level up or one level down.
+*/
+pub fn delta_distribution(dice_faces: u8, dice_mode: u8, single_die_mode: bool) -> Vec<(i32, Decimal)> {
+    if single_die_mode {
+        let up_rolls = (1..=dice_faces as i8)
+            .filter(|roll| single_die_step(*roll, dice_faces) > 0)
+            .count();
+        let up_chance = Decimal::from(up_rolls as i64) / dice_faces;
+        return vec![(1, up_chance), (-1, dec!("1") - up_chance)];
+    }
+
+    let house = dice_weights(dice_faces, dice_mode == DICE_MODE_HOUSE_FAVORED);
+    let player = dice_weights(dice_faces, dice_mode == DICE_MODE_PLAYER_FAVORED);
+    // weight of every delta from -(faces - 1) up to faces - 1
+    let offset = dice_faces as i32 - 1;
+    let mut delta_weights = vec![0i64; 2 * dice_faces as usize - 1];
+    for (h, house_weight) in house.iter().enumerate() {
+        for (p, player_weight) in player.iter().enumerate() {
+            delta_weights[(p as i32 - h as i32 + offset) as usize] += house_weight * player_weight;
+        }
+    }
+    let total = Decimal::from(house.iter().sum::<i64>() * player.iter().sum::<i64>());
+    let tie_chance = Decimal::from(delta_weights[offset as usize]) / total;
+
+    let mut scale = dec!("1");
+    let mut tie_power = dec!("1");
+    for _ in 0..MAX_REROLLS {
+        tie_power = tie_power * tie_chance;
+        scale = scale + tie_power;
+    }
+
+    let mut distribution: Vec<(i32, Decimal)> = delta_weights.iter().enumerate()
+        .map(|(index, weight)| (index as i32 - offset, Decimal::from(*weight) / total * scale))
+        .filter(|(delta, _)| *delta != 0)
+        .collect();
+    distribution.push((0, tie_power * tie_chance));
+    distribution
+}
+
+/*
+    Chance that a ticket on start_level reaches win_level before level 0, playing unstaked rounds
+    with the given level changes, limited to max_delta levels per round when max_delta is above 0.
+    The chances of the levels 1 up to win_level - 1 depend on each other linearly:
+    P(level) = sum of chance * P(level + delta), with P(0) = 0 and P(win_level) = 1.
+    This system is solved exactly by Gaussian elimination, no random walk is simulated.
+*/
+pub fn win_probability(distribution: &[(i32, Decimal)], start_level: i8, win_level: i8, max_delta: i8) 
+    -> Decimal {
+    let levels = win_level as usize - 1;
+    let mut matrix: Vec<Vec<Decimal>> = (0..levels)
+        .map(|row| (0..levels).map(|col| if row == col { dec!("1") } else { dec!("0") }).collect())
+        .collect();
+    let mut wins = vec![dec!("0"); levels];
+    for level in 1..win_level as i32 {
+        let row = level as usize - 1;
+        for (delta, chance) in distribution.iter() {
+            let mut delta = *delta;
+            if max_delta > 0 {
+                delta = delta.clamp(-(max_delta as i32), max_delta as i32);
+            }
+            let next_level = (level + delta).clamp(0, win_level as i32);
+            if next_level == win_level as i32 {
+                wins[row] = wins[row] + *chance;
+            } else if next_level > 0 {
+                let col = next_level as usize - 1;
+                matrix[row][col] = matrix[row][col] - *chance;
+            }
+        }
+    }
+
+    // forward elimination, the diagonal stays positive as every row keeps a chance to leave the level
+    for col in 0..levels {
+        let (eliminated, remaining) = matrix.split_at_mut(col + 1);
+        let (eliminated_wins, remaining_wins) = wins.split_at_mut(col + 1);
+        let pivot_row = &eliminated[col];
+        let pivot_win = eliminated_wins[col];
+        for (row, win) in remaining.iter_mut().zip(remaining_wins.iter_mut()) {
+            if row[col] == dec!("0") {
+                continue;
+            }
+            let factor = row[col] / pivot_row[col];
+            for (value, pivot_value) in row.iter_mut().zip(pivot_row.iter()).skip(col) {
+                *value = *value - factor * *pivot_value;
+            }
+            *win = *win - factor * pivot_win;
+        }
+    }
+
+    // back substitution
+    let mut chances = vec![dec!("0"); levels];
+    for row in (0..levels).rev() {
+        let known = matrix[row].iter().zip(chances.iter()).skip(row + 1)
+            .fold(dec!("0"), |sum, (value, chance)| sum + *value * *chance);
+        chances[row] = (wins[row] - known) / matrix[row][row];
+    }
+    chances[start_level as usize - 1]
 }
+
 #[blueprint]
 mod mod_radicex{
     struct Radicex {
-        // vault to store radix, buy-in go here and prices are redeemed from here.
+        // vault to store radix, buy-in go here.
         radix_vault: Vault,
+
+        // vault with the prize resource, prices are redeemed from here.
+        prize_vault: Vault,
         
+        // progressive jackpot, fed by every ticket sale and won together with the prize on the win level
+        jackpot_vault: Vault,
+
+        // part of every ticket sale that goes to the jackpot
+        jackpot_fraction: Decimal,
+
+        // operator fees, kept apart from the main wallet
+        fee_vault: Vault,
+
+        // part of every ticket sale that goes to the fee vault
+        fee_fraction: Decimal,
+
+        // redeem fees of prizes paid in another resource than the accepted buy-in,
+        // these can't go in the fee vault
+        prize_fee_vault: Vault,
+
+        // resource accepted for buy-ins, stakes and deposits, held by the main wallet, jackpot and fee vault.
+        // XRD unless another resource, e.g. a stablecoin, was set on instantiation
+        accepted_buyin: ResourceAddress,
+
         // resourceaddress of the NFT ticket, used for NFT creation and various authorization
         my_non_fungible_ticket: ResourceAddress,
 
         // admin vault, this contains a badge for various inner dApp permission handling
         admin_vault: Vault,
 
+        // badge allowed to mint tickets, None when the ticket resource predates the separate badges
+        minter_vault: Option<Vault>,
+
+        // badge allowed to update and burn tickets, None when the ticket resource predates the separate badges
+        updater_vault: Option<Vault>,
+
         // keep track of the number of NFTs generated, this number will be used for the NFT-Id
         nrNFTsgenerated: u64,
+
+        // maximum number of tickets that can be minted, 0 means unlimited
+        max_tickets: u64,
+
+        // ticket level at which a ticket wins and can be redeemed
+        win_level: i8,
+
+        // level of a new or reinitialized ticket
+        start_level: i8,
+
+        // amount of the prize resource paid out when a winning ticket is redeemed
+        prize_amount: Decimal,
+
+        // intermediate prizes as (level, amount), paid when a ticket below the win level is redeemed
+        prize_tiers: Vec<(i8, Decimal)>,
+
+        // fixed-odds payouts as (player die - house die, stake multiplier), empty unless fixed-odds
+        payout_table: Vec<(i8, Decimal)>,
+
+        // amount of XRD needed to buy a new ticket
+        ticket_price: Decimal,
+
+        // part of the ticket price paid to reinitialize a level 0 ticket, 0.9 means a 10% discount
+        reinit_discount: Decimal,
+
+        // which die is loaded, see DICE_MODE_FAIR, DICE_MODE_PLAYER_FAVORED and DICE_MODE_HOUSE_FAVORED
+        dice_mode: u8,
+
+        // number of faces of the dice, 6 for classic dice
+        dice_faces: u8,
+
+        // roll a single die against a fixed threshold instead of the house die, see single_die_step
+        single_die_mode: bool,
+
+        // when paused, no tickets can be bought, reinitialized or played
+        paused: bool,
+
+        // number of epochs a ticket has to wait between rounds, 0 disables the cooldown
+        cooldown_epochs: u64,
+
+        // largest level change of a single round, 0 disables the limit
+        max_delta: i8,
+
+        // part of every redeemed prize kept as operator fee, the jackpot is paid in full
+        redeem_fee_fraction: Decimal,
+
+        // number of epochs between any two prize redemptions, 0 disables the redeem cooldown
+        redeem_cooldown_epochs: u64,
+
+        // prizes are doubled while the current epoch is below this epoch, 0 means no promo
+        double_prize_until_epoch: u64,
+
+        // epoch of the last prize redemption of any ticket, None before the first redemption
+        last_redeem_epoch: Option<u64>,
+
+        // amount of XRD refunded when a level 0 ticket is handed in
+        dead_ticket_refund: Decimal,
+
+        // last RECENT_GLOBAL_THROWS throws of all tickets, oldest first
+        recent_global_throws: Vec<String>,
+
+        // how a random number is turned into a die roll, see RNG_STRATEGY_REJECTION
+        rng_strategy: u8,
+
+        // (house, player) dice forced for testing, None rolls the dice as usual
+        rng_override: Option<(i8, i8)>,
+
+        // whether set_rng_override may force the dice, fixed at instantiation and false in production
+        allow_rng_override: bool,
+
+        // part of the ticket price accrued for the referrer ticket of a referred sale
+        referral_reward: Decimal,
+
+        // referral rewards, until they are claimed with claim_referral
+        referral_vault: Vault,
+
+        // unclaimed referral rewards per referrer ticket
+        referral_balances: KeyValueStore<NonFungibleLocalId, Decimal>,
+
+        // hash of the last transaction minting tickets
+        minting_transaction: Hash,
+
+        // tickets minted in the minting transaction, they can't be the referrer of a sale in it
+        minted_in_transaction: Vec<NonFungibleLocalId>,
+
+        // number of won rounds in a row granting one extra level, 0 disables the streak bonus
+        streak_bonus_threshold: u8,
+
+        // amount of XRD set aside for a ticket dropping to level 0, 0 disables the consolation
+        bust_consolation: Decimal,
+
+        // XRD set aside for busted tickets, until it is claimed with claim_consolation
+        consolation_vault: Vault,
+
+        // unclaimed consolation per ticket
+        consolations: KeyValueStore<NonFungibleLocalId, Decimal>,
+
+        // amount of XRD withdrawals have to leave in the main wallet
+        min_reserve: Decimal,
+
+        // total amount of XRD paid for buying and reinitializing tickets
+        total_buyins: Decimal,
+
+        // total amount of prize resource paid out by redeem_prize, jackpots are not included
+        total_prizes_paid: Decimal,
+
+        // number of tickets redeemed for a prize
+        total_wins: u64,
+
+        // number of tickets on the win level, each can be redeemed for the prize amount
+        tickets_at_max: u64,
+
+        // leaderboard season, high scores of earlier seasons are kept but no longer current
+        season: u32,
+
+        // highest level reached by each played ticket per (season, ticket), for the leaderboard
+        high_scores: KeyValueStore<(u32, NonFungibleLocalId), i8>,
     }
 
     impl Radicex {
         // Implement the functions and methods which will manage those resources and data
         // This is a function, and can be called directly on the blueprint once deployed
-        pub fn instantiate() -> (ComponentAddress, Bucket) {
+        pub fn instantiate(win_level: i8, start_level: i8, prize_resource: ResourceAddress, 
+            prize_amount: Decimal, ticket_price: Decimal, reinit_discount: Decimal, dice_mode: u8, 
+            dice_faces: u8, single_die_mode: bool, max_tickets: u64) -> (ComponentAddress, Bucket) {
+            Self::instantiate_with_tiers(win_level, start_level, prize_resource, prize_amount, Vec::new(), 
+                ticket_price, reinit_discount, dice_mode, dice_faces, single_die_mode, max_tickets, 
+                None, None, None, None, true, false)
+        }
+
+        /*
+            Instantiate with intermediate prize tiers below the win level, e.g. [(10, 1), (18, 2)]
+            A ticket on a tier level can be redeemed for that tier's prize, or played on.
+            The description, icon url and tags of the ticket resource can be set for wallets,
+            None keeps the default description and tags and leaves out the icon.
+            Buy-ins can be accepted in another resource than XRD, e.g. a stablecoin, None accepts XRD.
+            public_preview lets anyone call roll_dice_preview, false denies the preview to everyone.
+            allow_rng_override enables set_rng_override to force the dice, for test deployments only.
+        */
+        pub fn instantiate_with_tiers(win_level: i8, start_level: i8, prize_resource: ResourceAddress, 
+            prize_amount: Decimal, prize_tiers: Vec<(i8, Decimal)>, ticket_price: Decimal, reinit_discount: Decimal, 
+            dice_mode: u8, dice_faces: u8, single_die_mode: bool, max_tickets: u64, 
+            ticket_description: Option<String>, 
+            ticket_icon_url: Option<String>, ticket_tags: Option<Vec<String>>, 
+            accepted_buyin: Option<ResourceAddress>, public_preview: bool, allow_rng_override: bool) 
+            -> (ComponentAddress, Bucket) {
+            Self::instantiate_game(win_level, start_level, prize_resource, prize_amount, prize_tiers, 
+                ticket_price, reinit_discount, dice_mode, dice_faces, single_die_mode, max_tickets, 
+                ticket_description, ticket_icon_url, ticket_tags, accepted_buyin, public_preview, 
+                allow_rng_override, Vec::new())
+        }
+
+        /*
+            Instantiate a fixed-odds game, played with play_instant instead of chasing the win level.
+            Every round is a single throw, the stake is multiplied by the payout of the outcome 
+            (player die - house die) in the payout table, e.g. [(5, 6), (3, 3), (1, 1.5)].
+            Outcomes missing from the table pay nothing. Stakes and payouts use the main wallet,
+            in XRD or the given buy-in resource. There are no tickets, minting, playing and
+            reinitializing tickets fail. allow_rng_override is for test deployments only.
+        */
+        pub fn instantiate_fixed_odds(dice_mode: u8, dice_faces: u8, payout_table: Vec<(i8, Decimal)>, 
+            accepted_buyin: Option<ResourceAddress>, allow_rng_override: bool) -> (ComponentAddress, Bucket) {
+            assert!(!payout_table.is_empty(), "The payout table of a fixed-odds game can not be empty");
+            Self::instantiate_game(25, 10, accepted_buyin.unwrap_or(RADIX_TOKEN), dec!("5"), Vec::new(), 
+                dec!("1"), dec!("0.9"), dice_mode, dice_faces, false, 0, None, None, None, accepted_buyin, 
+                true, allow_rng_override, payout_table)
+        }
+
+        /*
+            Mint the admin badges, the internal badges and the ticket resource of a new game 
+            and instantiate its component, shared by instantiate_with_tiers and instantiate_fixed_odds.
+        */
+        fn instantiate_game(win_level: i8, start_level: i8, prize_resource: ResourceAddress, 
+            prize_amount: Decimal, prize_tiers: Vec<(i8, Decimal)>, ticket_price: Decimal, 
+            reinit_discount: Decimal, dice_mode: u8, dice_faces: u8, single_die_mode: bool, 
+            max_tickets: u64, ticket_description: Option<String>, 
+            ticket_icon_url: Option<String>, ticket_tags: Option<Vec<String>>, 
+            accepted_buyin: Option<ResourceAddress>, public_preview: bool, allow_rng_override: bool, 
+            payout_table: Vec<(i8, Decimal)>) -> (ComponentAddress, Bucket) {
 
             // creating our admin badges
-            // use one badge for internal admin stuff, and send one to instantiate wallet address.
+            // use one badge for internal admin stuff, and send two to instantiate wallet address.
+            // The operator can hand one of those to a second admin.
             let mut my_admin_badge = ResourceBuilder::new_fungible()
                 .divisibility(DIVISIBILITY_NONE)
                 .metadata("name", "Admin Badge for RaDiceX")
-                .mint_initial_supply(2);
+                .mint_initial_supply(3);
 
             // put one admin badge and put in in the admin vault
             let local_admin_badge: Bucket = my_admin_badge.take(1);
 
-            let admin_rule: AccessRule = rule!(require(my_admin_badge.resource_address()));
+            // separate internal badges for minting and for updating and burning tickets,
+            // so each operation only runs with the rights it needs.
+            // Burning shares the updater badge: the split that matters is creating tickets versus changing
+            // existing ones, and burning only ever ends an existing ticket, like redeeming resets its level.
+            // The admin badge keeps all rights, e.g. for a component taking over the tickets.
+            let minter_badge = ResourceBuilder::new_fungible()
+                .divisibility(DIVISIBILITY_NONE)
+                .metadata("name", "Ticket Minter Badge for RaDiceX")
+                .mint_initial_supply(1);
+            let updater_badge = ResourceBuilder::new_fungible()
+                .divisibility(DIVISIBILITY_NONE)
+                .metadata("name", "Ticket Updater Badge for RaDiceX")
+                .mint_initial_supply(1);
+            let mint_rule: AccessRule = rule!(require(minter_badge.resource_address()) 
+                || require(my_admin_badge.resource_address()));
+            let update_rule: AccessRule = rule!(require(updater_badge.resource_address()) 
+                || require(my_admin_badge.resource_address()));
 
             // Create our Ticket NFT
-            let my_non_fungible_ticket = ResourceBuilder::new_integer_non_fungible()
+            let description = ticket_description
+                .unwrap_or_else(|| "Your ticket for RaDiceX, it keeps track of your game progress".to_string());
+            // metadata values are strings, the tags are stored comma separated
+            let tags = ticket_tags
+                .map(|tags| tags.join(","))
+                .unwrap_or_else(|| "game,dice".to_string());
+            let mut ticket_builder = ResourceBuilder::new_integer_non_fungible()
                 .metadata("name", "Ticket for RaDiceX")
-                .burnable(admin_rule.clone(), LOCKED)
-                .mintable(rule!(require(my_admin_badge.resource_address())), LOCKED)
-                .updateable_non_fungible_data(admin_rule.clone(), LOCKED)
+                .metadata("description", description)
+                .metadata("tags", tags);
+            if let Some(icon_url) = ticket_icon_url {
+                ticket_builder = ticket_builder.metadata("icon_url", icon_url);
+            }
+            let my_non_fungible_ticket = ticket_builder
+                .burnable(update_rule.clone(), LOCKED)
+                .mintable(mint_rule, LOCKED)
+                .updateable_non_fungible_data(update_rule, LOCKED)
                 .restrict_withdraw(rule!(allow_all), LOCKED)
                 .restrict_deposit(AccessRule::AllowAll, LOCKED)
                 .create_with_no_initial_supply();
 
+            let component = Self::instantiate_component(my_non_fungible_ticket, local_admin_badge, 
+                Some((minter_badge, updater_badge)), 0, win_level, start_level, prize_resource, 
+                prize_amount, prize_tiers, ticket_price, reinit_discount, dice_mode, dice_faces, 
+                single_die_mode, max_tickets, accepted_buyin.unwrap_or(RADIX_TOKEN), public_preview, 
+                allow_rng_override, payout_table);
+
+            // Return the instantiated component and the two admin badges that were just minted
+            (component, my_admin_badge)
+        }
+
+        /*
+            Instantiate a component serving an existing ticket resource, e.g. when redeploying,
+            so the tickets of the old component can still be played and redeemed.
+            Prerequisites:
+            - admin_badge is one badge of the resource that can mint, update and burn the tickets,
+              the internal admin badge of the old component can be taken out with withdraw_admin_badge.
+              The admin methods of the new component need a proof of the same badge resource.
+            - nr_tickets_minted is the total_tickets_minted of the old component,
+              so new tickets don't reuse the id of an existing ticket.
+            Returns the new component, no new admin badges are minted.
+        */
+        pub fn instantiate_with_resource(ticket_resource: ResourceAddress, admin_badge: Bucket, 
+            nr_tickets_minted: u64, win_level: i8, start_level: i8, prize_resource: ResourceAddress, 
+            prize_amount: Decimal, ticket_price: Decimal, reinit_discount: Decimal, dice_mode: u8, 
+            dice_faces: u8, single_die_mode: bool, max_tickets: u64) -> ComponentAddress {
+            assert!(admin_badge.amount() == dec!("1"), "Exactly one (1) admin badge has to be supplied");
+            Self::instantiate_component(ticket_resource, admin_badge, None, nr_tickets_minted, 
+                win_level, start_level, prize_resource, prize_amount, Vec::new(), ticket_price, 
+                reinit_discount, dice_mode, dice_faces, single_die_mode, max_tickets, RADIX_TOKEN, 
+                true, false, Vec::new())
+        }
+
+        /*
+            Validate the configuration and globalize a component for the given ticket resource,
+            local_admin_badge goes in the admin vault and its resource guards the admin methods.
+            ticket_badges are the (minter, updater) badges of the tickets, None uses the admin badge.
+        */
+        fn instantiate_component(my_non_fungible_ticket: ResourceAddress, local_admin_badge: Bucket, 
+            ticket_badges: Option<(Bucket, Bucket)>, nr_tickets_minted: u64, win_level: i8, 
+            start_level: i8, prize_resource: ResourceAddress, prize_amount: Decimal, 
+            prize_tiers: Vec<(i8, Decimal)>, ticket_price: Decimal, reinit_discount: Decimal, 
+            dice_mode: u8, dice_faces: u8, single_die_mode: bool, max_tickets: u64, 
+            accepted_buyin: ResourceAddress, public_preview: bool, allow_rng_override: bool, 
+            payout_table: Vec<(i8, Decimal)>) -> ComponentAddress {
+
+            assert!(win_level > 0, "The win level must be greater than 0");
+            // a ticket starting on the win level would be redeemable right after minting
+            assert!(start_level > 0 && start_level < win_level, 
+                "The start level must be between 0 and the win level {}, got {}", win_level, start_level);
+            assert!(prize_amount >= dec!("0"), "The prize amount can not be negative");
+            Self::assert_ticket_price(ticket_price);
+            Self::assert_reinit_discount(reinit_discount);
+            assert!(dice_mode <= DICE_MODE_HOUSE_FAVORED, 
+                "The dice mode must be 0 (fair), 1 (player favored) or 2 (house favored)");
+            assert!((MIN_DICE_FACES..=MAX_DICE_FACES).contains(&dice_faces), 
+                "The dice must have between {} and {} faces, got {}", 
+                MIN_DICE_FACES, MAX_DICE_FACES, dice_faces);
+            // a loaded die only favours high rolls, which can't favour the house against a threshold
+            assert!(!single_die_mode || dice_mode == DICE_MODE_FAIR, 
+                "The single die mode can only be played with a fair die");
+            for (level, amount) in prize_tiers.iter() {
+                assert!(*level > 0 && *level < win_level, 
+                    "Prize tier level {} must be between 0 and the win level {}", level, win_level);
+                assert!(*amount >= dec!("0"), "The prize amount of tier {} can not be negative", level);
+            }
+            let max_outcome = dice_faces as i8 - 1;
+            for (index, (outcome, multiplier)) in payout_table.iter().enumerate() {
+                assert!((-max_outcome..=max_outcome).contains(outcome), 
+                    "Payout outcome {} must be between -{} and {}", outcome, max_outcome, max_outcome);
+                // ties are thrown again, an outcome of 0 never pays
+                assert!(*outcome != 0, "Payout outcome 0 can not be listed, ties are thrown again");
+                assert!(*multiplier >= dec!("0"), "The payout of outcome {} can not be negative", outcome);
+                assert!(payout_table[..index].iter().all(|(other, _)| other != outcome), 
+                    "Payout outcome {} is listed more than once", outcome);
+            }
+
+            let admin_rule: AccessRule = rule!(require(local_admin_badge.resource_address()));
+
+            // withdrawals need a proof of both handed out admin badges, 
+            // so a single compromised badge can't drain the vault.
+            let withdrawal_rule: AccessRule = 
+                rule!(require_amount(dec!("2"), local_admin_badge.resource_address()));
+
+            // the operator decides whether anyone can preview the dice roller
+            let preview_rule: AccessRule = if public_preview { rule!(allow_all) } else { rule!(deny_all) };
+
             // set the access rules for the Admin-only and internal functions.
             let access_rules = AccessRules::new()
                 .method("admin_ticket", admin_rule.clone(), AccessRule::DenyAll)
-                .method("withdrawal_all", admin_rule.clone(), rule!(deny_all))
-                .method("roll_dice", rule!(deny_all), rule!(deny_all))
+                .method("withdrawal_all", withdrawal_rule.clone(), rule!(deny_all))
+                .method("withdraw", withdrawal_rule.clone(), rule!(deny_all))
+                .method("withdraw_to", withdrawal_rule.clone(), rule!(deny_all))
+                .method("sweep_to", withdrawal_rule.clone(), rule!(deny_all))
+                .method("shutdown", withdrawal_rule.clone(), rule!(deny_all))
+                .method("withdraw_admin_badge", withdrawal_rule.clone(), rule!(deny_all))
+                .method("withdraw_ticket_badges", withdrawal_rule.clone(), rule!(deny_all))
+                .method("set_paused", admin_rule.clone(), rule!(deny_all))
+                .method("set_reinit_discount", admin_rule.clone(), rule!(deny_all))
+                .method("set_cooldown_epochs", admin_rule.clone(), rule!(deny_all))
+                .method("set_max_delta", admin_rule.clone(), rule!(deny_all))
+                .method("set_redeem_cooldown_epochs", admin_rule.clone(), rule!(deny_all))
+                .method("set_double_prize_until_epoch", admin_rule.clone(), rule!(deny_all))
+                .method("fund_prize_vault", admin_rule.clone(), rule!(deny_all))
+                .method("set_dead_ticket_refund", admin_rule.clone(), rule!(deny_all))
+                .method("set_bust_consolation", admin_rule.clone(), rule!(deny_all))
+                .method("set_streak_bonus_threshold", admin_rule.clone(), rule!(deny_all))
+                .method("set_referral_reward", admin_rule.clone(), rule!(deny_all))
+                .method("set_rng_override", admin_rule.clone(), rule!(deny_all))
+                .method("set_rng_strategy", admin_rule.clone(), rule!(deny_all))
+                .method("set_prize_amount", admin_rule.clone(), rule!(deny_all))
+                .method("set_ticket_price", admin_rule.clone(), rule!(deny_all))
+                .method("reset_season", admin_rule.clone(), rule!(deny_all))
+                .method("set_redeem_fee_fraction", admin_rule.clone(), rule!(deny_all))
+                .method("set_min_reserve", admin_rule.clone(), rule!(deny_all))
+                .method("set_fee_fraction", admin_rule.clone(), rule!(deny_all))
+                .method("collect_fees", admin_rule.clone(), rule!(deny_all))
+                .method("collect_prize_fees", admin_rule.clone(), rule!(deny_all))
+                .method("set_ticket_level", admin_rule.clone(), rule!(deny_all))
+                .method("set_branding", admin_rule.clone(), rule!(deny_all))
+                .method("mint_promo_ticket", admin_rule.clone(), rule!(deny_all))
+                .method("mint_promo_batch", admin_rule.clone(), rule!(deny_all))
+                .method("gift_ticket", admin_rule.clone(), rule!(deny_all))
+                .method("deposit_admin_badge", admin_rule.clone(), rule!(deny_all))
+                .method("deposit_ticket_badges", admin_rule.clone(), rule!(deny_all))
+                .method("roll_dice_preview", preview_rule, rule!(deny_all))
                 .default(AccessRule::AllowAll, AccessRule::DenyAll);
                 
+            let (minter_vault, updater_vault) = match ticket_badges {
+                Some((minter_badge, updater_badge)) => 
+                    (Some(Vault::with_bucket(minter_badge)), Some(Vault::with_bucket(updater_badge))),
+                None => (None, None),
+            };
+
             let mut component = Self {
-                radix_vault: Vault::new(RADIX_TOKEN),
+                radix_vault: Vault::new(accepted_buyin),
+                prize_vault: Vault::new(prize_resource),
+                jackpot_vault: Vault::new(accepted_buyin),
+                jackpot_fraction: dec!("0.1"),
+                fee_vault: Vault::new(accepted_buyin),
+                fee_fraction: dec!("0"),
+                prize_fee_vault: Vault::new(prize_resource),
+                accepted_buyin,
                 my_non_fungible_ticket,
                 admin_vault: Vault::with_bucket(local_admin_badge),
-                nrNFTsgenerated: 0,
+                minter_vault,
+                updater_vault,
+                nrNFTsgenerated: nr_tickets_minted,
+                max_tickets,
+                win_level,
+                start_level,
+                prize_amount,
+                prize_tiers,
+                payout_table,
+                ticket_price,
+                reinit_discount,
+                dice_mode,
+                dice_faces,
+                single_die_mode,
+                paused: false,
+                cooldown_epochs: 0,
+                max_delta: 0,
+                redeem_fee_fraction: dec!("0"),
+                redeem_cooldown_epochs: 0,
+                double_prize_until_epoch: 0,
+                last_redeem_epoch: None,
+                dead_ticket_refund: dec!("0"),
+                recent_global_throws: Vec::new(),
+                rng_strategy: RNG_STRATEGY_REJECTION,
+                rng_override: None,
+                allow_rng_override,
+                referral_reward: dec!("0"),
+                referral_vault: Vault::new(accepted_buyin),
+                referral_balances: KeyValueStore::new(),
+                minting_transaction: Runtime::transaction_hash(),
+                minted_in_transaction: Vec::new(),
+                streak_bonus_threshold: 0,
+                bust_consolation: dec!("0"),
+                consolation_vault: Vault::new(accepted_buyin),
+                consolations: KeyValueStore::new(),
+                min_reserve: dec!("0"),
+                total_buyins: dec!("0"),
+                total_prizes_paid: dec!("0"),
+                total_wins: 0,
+                tickets_at_max: 0,
+                season: 1,
+                high_scores: KeyValueStore::new(),
             }
           
             .instantiate();
             component.add_access_check(access_rules);
-            let component = component.globalize();
+            component.globalize()
+        }
+
+        /*
+            Die roll function, used internally by roll_dice_preview
+            Private, so it can't be called from outside the component.
+            Uses rejection sampling on the uuid bits by default, so every face is equally likely on a fair die,
+            or the uuid modulo the faces when the modulo strategy is set.
+        */
+        fn roll_dice(&self, loaded: bool) -> i8 {
+            roll_die_with_strategy(Runtime::generate_uuid, self.dice_faces, loaded, self.rng_strategy)
+        }
+
+        /*
+            Switch between RNG_STRATEGY_REJECTION and RNG_STRATEGY_MODULO without redeploying
+            Admin only function.
+        */
+        pub fn set_rng_strategy(&mut self, strategy: u8) {
+            assert!(strategy <= RNG_STRATEGY_MODULO, 
+                "The rng strategy must be 0 (rejection sampling) or 1 (modulo)");
+            self.rng_strategy = strategy;
+        }
+
+        /*
+            Returns the strategy used to roll the dice, see RNG_STRATEGY_REJECTION and RNG_STRATEGY_MODULO
+        */
+        pub fn rng_strategy(&self) -> u8 {
+            self.rng_strategy
+        }
+
+        /*
+            Roll a single fair die, so anyone can check the rolls are uniform
+            Only callable when the component was instantiated with a public preview.
+        */
+        pub fn roll_dice_preview(&self) -> i8 {
+            self.roll_dice(false)
+        }
+
+        /*
+            Play one instant round of a fixed-odds game, see instantiate_fixed_odds
+            The stake goes to the main wallet, the stake times the payout of the thrown outcome is returned.
+            The main wallet has to cover the highest payout of the table before the dice are thrown.
+        */
+        pub fn play_instant(&mut self, stake: Bucket) -> Bucket {
+            assert!(!self.paused, "Game is paused");
+            assert!(!self.payout_table.is_empty(), "This is not a fixed-odds game, there is no payout table");
+            assert!(
+                stake.resource_address() == self.accepted_buyin,
+                "The stake can only be done with the accepted buy-in resource"
+            );
+            assert!(!stake.is_empty(), "The supplied bucket is empty");
+
+            let stake_amount = stake.amount();
+            let max_multiplier = self.payout_table.iter()
+                .fold(dec!("0"), |max, (_, multiplier)| if *multiplier > max { *multiplier } else { max });
+            let max_payout = stake_amount * max_multiplier;
+            // the minimum reserve is kept for refunds and consolations, it doesn't back payouts
+            assert!(self.available_above_reserve() + stake_amount >= max_payout, 
+                "Not enough funds in the vault to cover the highest payout of {}", max_payout);
+            self.radix_vault.put(stake);
+
+            let (house_die, player_die, delta) = self.throw_round();
+            let multiplier = self.payout_table.iter()
+                .find(|(outcome, _)| *outcome == delta)
+                .map(|(_, multiplier)| *multiplier)
+                .unwrap_or(dec!("0"));
+            let payout = self.round_to_divisibility(stake_amount * multiplier, RoundingMode::TowardsZero);
+            Runtime::emit_event(InstantPlayed {
+                house_die,
+                player_die,
+                stake: stake_amount,
+                payout,
+            });
+
+            let payout_bucket = self.radix_vault.take(payout);
+            self.vault_changed("instant");
+            payout_bucket
+        }
+
+        /*
+            Returns the payout table as (player die - house die, stake multiplier), empty unless fixed-odds
+        */
+        pub fn payout_table(&self) -> Vec<(i8, Decimal)> {
+            self.payout_table.clone()
+        }
+
+        /*
+            Practice mode, rolls the dice of a round and returns (house, player, level delta)
+            No ticket or vault is touched, the round is purely informational.
+            Runtime::generate_uuid doesn't need any component state, so this works on &self.
+        */
+        pub fn simulate_round(&self) -> (i8, i8, i8) {
+            self.throw_round()
+        }
+
+        /*
+            Throw the dice of a round and return (house, player, level delta) for an unstaked round.
+            In single die mode the house "die" is the fixed threshold the player die has to beat.
+            Unless forced with set_rng_override every die draws its own uuids, published in a DiceDrawn event.
+        */
+        fn throw_round(&self) -> (i8, i8, i8) {
+            let (house_die, player_die) = match self.rng_override {
+                Some((_, player_die)) if self.single_die_mode => 
+                    (single_die_threshold(self.dice_faces), player_die),
+                Some(dice) => dice,
+                None => {
+                    let mut raws: Vec<u128> = Vec::new();
+                    let random_source = || {
+                        let raw = Runtime::generate_uuid();
+                        raws.push(raw);
+                        raw
+                    };
+                    let (house_die, player_die) = throw_with_source(random_source, self.dice_faces, 
+                        self.dice_mode, self.single_die_mode, self.rng_strategy);
+                    Runtime::emit_event(DiceDrawn { raws, house_die, player_die });
+                    (house_die, player_die)
+                }
+            };
+            if self.single_die_mode {
+                return (house_die, player_die, single_die_step(player_die, self.dice_faces));
+            }
+            (house_die, player_die, player_die - house_die)
+        }
+
+        /*
+            Force the (house, player) dice of every following round, so tests can check exact outcomes.
+            None, the default, rolls the dice with Runtime::generate_uuid again.
+            Every change emits a RngOverrideSet event, the current override is public with rng_override.
+            Only available when instantiated with allow_rng_override, so production games can't force the dice.
+            Admin only function, meant for testing.
+        */
+        pub fn set_rng_override(&mut self, rng_override: Option<(i8, i8)>) {
+            assert!(self.allow_rng_override, "Forcing the dice is disabled for this game");
+            if let Some((house_die, player_die)) = rng_override {
+                let faces = 1..=self.dice_faces as i8;
+                assert!(faces.contains(&house_die) && faces.contains(&player_die), 
+                    "The forced dice must be between 1 and {}", self.dice_faces);
+            }
+            self.rng_override = rng_override;
+            Runtime::emit_event(RngOverrideSet { rng_override });
+        }
+
+        /*
+            Returns the forced (house, player) dice, None when the dice are rolled
+        */
+        pub fn rng_override(&self) -> Option<(i8, i8)> {
+            self.rng_override
+        }
+
+        /*
+            Deposit x coins in the main wallet, which pays refunds, consolations and instant payouts.
+            Prizes are paid from the prize vault, fund that with fund_prize_vault.
+            The rest of the bucket is returned.
+        */
+        pub fn deposit(&mut self, amount: Decimal, mut deposit: Bucket) -> Bucket {
+
+            assert!(!deposit.is_empty(), "The supplied bucket is empty");
+            assert!(
+                deposit.resource_address() == self.accepted_buyin,
+                "The Buy-in can only be done with the accepted buy-in resource"
+            );
+            assert!(deposit.amount()>=amount, 
+                "There are not enough tokens in your account, supplied {} but requested {}", 
+                deposit.amount(), amount);
+
+            let xrd_deposit = deposit.take(amount);
+            self.radix_vault.put(xrd_deposit);
+            self.vault_changed("deposit");
+
+            // return the bucket incase of a surplus of tokens
+            deposit
+        }
+
+        /*
+            Top up the main wallet with the whole bucket of XRD, nothing is returned.
+        */
+        pub fn fund_house(&mut self, funds: Bucket) {
+            assert!(
+                funds.resource_address() == self.accepted_buyin,
+                "The house can only be funded with the accepted buy-in resource"
+            );
+            self.radix_vault.put(funds);
+            self.vault_changed("fund_house");
+        }
+
+        /*
+            Returns the amount of XRD in the main wallet
+        */
+        pub fn vault_balance(&self) -> Decimal {
+            self.radix_vault.amount()
+        }
+
+        /*
+            Returns the amount of the prize resource available for paying prizes
+        */
+        pub fn prize_vault_balance(&self) -> Decimal {
+            self.prize_vault.amount()
+        }
+
+        /*
+            Returns the amount of XRD in the fee vault, to be collected by the operator
+        */
+        pub fn fee_balance(&self) -> Decimal {
+            self.fee_vault.amount()
+        }
+
+        /*
+            Returns the redeem fees in the prize resource when prizes aren't paid in the accepted buy-in
+        */
+        pub fn prize_fee_balance(&self) -> Decimal {
+            self.prize_fee_vault.amount()
+        }
+
+        /*
+            Returns the total amount of prize resource paid out to redeemed tickets
+        */
+        pub fn total_prizes_paid(&self) -> Decimal {
+            self.total_prizes_paid
+        }
+
+        /*
+            Returns whether the prize vault can currently pay the prize of a winning ticket
+        */
+        pub fn can_pay_prize(&self) -> bool {
+            self.prize_vault.amount() >= self.prize_amount
+        }
+
+        /*
+            Returns the amount of prize resource needed to pay all tickets on the win level,
+            the prize vault should hold at least this much. Prize tiers and the jackpot are not included.
+        */
+        pub fn outstanding_prize_liability(&self) -> Decimal {
+            self.prize_amount * self.tickets_at_max
+        }
+
+        /*
+            Returns the XRD paid for buying and reinitializing tickets
+        */
+        pub fn total_buyins(&self) -> Decimal {
+            self.total_buyins
+        }
+
+        /*
+            Returns the buy-ins minus the prizes paid, the net result of the game.
+            Only meaningful when prizes are paid in XRD, jackpots and fees are not included.
+        */
+        pub fn net_revenue(&self) -> Decimal {
+            self.total_buyins - self.total_prizes_paid
+        }
+
+        /*
+            Returns the number of tickets redeemed for a prize
+        */
+        pub fn total_wins(&self) -> u64 {
+            self.total_wins
+        }
+
+        /*
+            Returns the expected level change of an unstaked round with the current dice,
+            so players can see the house edge. See expected_delta.
+        */
+        pub fn expected_delta(&self) -> Decimal {
+            if self.single_die_mode {
+                let total_steps: i64 = (1..=self.dice_faces as i8)
+                    .map(|roll| single_die_step(roll, self.dice_faces) as i64)
+                    .sum();
+                return (Decimal::from(total_steps) / self.dice_faces)
+                    .round(2, RoundingMode::TowardsNearestAndHalfAwayFromZero);
+            }
+            expected_delta(self.dice_faces, self.dice_mode)
+        }
+
+        /*
+            Theoretical return to player of a new ticket, the long-run prize paid per unit of ticket price:
+            the chance a ticket climbs from the start level to the win level times the prize after
+            the redeem fee, divided by the ticket price. See delta_distribution and win_probability.
+            Assumptions: a ticket plays unstaked rounds until the win level or level 0, the prize resource
+            is worth the same as the buy-in resource, and prize tiers, the jackpot, streak bonuses,
+            consolations, refunds and renewals are left out. Rounded to four decimals.
+        */
+        pub fn theoretical_rtp(&self) -> Decimal {
+            let distribution = delta_distribution(self.dice_faces, self.dice_mode, self.single_die_mode);
+            let win_chance = win_probability(&distribution, self.start_level, self.win_level, self.max_delta);
+            (win_chance * self.prize_amount * (dec!("1") - self.redeem_fee_fraction) / self.ticket_price)
+                .round(4, RoundingMode::TowardsNearestAndHalfAwayFromZero)
+        }
+
+        /*
+            Returns the amount of XRD in the jackpot, paid on top of the prize for the next winning ticket
+        */
+        pub fn jackpot_balance(&self) -> Decimal {
+            self.jackpot_vault.amount()
+        }
+
+        /*
+            Returns the configuration as (ticket price, prize amount, start level, win level),
+            so a frontend can fetch all tunables in one call
+        */
+        pub fn get_config(&self) -> (Decimal, Decimal, i8, i8) {
+            (self.ticket_price, self.prize_amount, self.start_level, self.win_level)
+        }
+
+        /*
+            Returns the configuration and live state in one call, enough to power a dashboard
+        */
+        pub fn snapshot(&self) -> GameSnapshot {
+            GameSnapshot {
+                vault_balance: self.radix_vault.amount(),
+                prize_vault_balance: self.prize_vault.amount(),
+                jackpot_balance: self.jackpot_vault.amount(),
+                tickets_minted: self.nrNFTsgenerated,
+                tickets_at_max: self.tickets_at_max,
+                paused: self.paused,
+                ticket_price: self.ticket_price,
+                prize_amount: self.prize_amount,
+                start_level: self.start_level,
+                win_level: self.win_level,
+                dice_mode: self.dice_mode,
+                dice_faces: self.dice_faces,
+                single_die_mode: self.single_die_mode,
+                max_tickets: self.max_tickets,
+            }
+        }
+
+        /*
+            Returns the resource address of the ticket NFT, needed to create ticket proofs
+        */
+        pub fn ticket_resource_address(&self) -> ResourceAddress {
+            self.my_non_fungible_ticket
+        }
+
+        /*
+            Returns the number of tickets minted over the lifetime of the component.
+            Burned tickets are still counted, so this is not the number of tickets in circulation.
+        */
+        pub fn total_tickets_minted(&self) -> u64 {
+            self.nrNFTsgenerated
+        }
+
+        /*
+            Returns the number of tickets which can still be minted under the cap, None when unlimited.
+            Saturates at 0, a component instantiated with an existing ticket resource may start above the cap.
+        */
+        pub fn remaining_mints(&self) -> Option<u64> {
+            if self.max_tickets == 0 {
+                return None;
+            }
+            Some(self.max_tickets.saturating_sub(self.nrNFTsgenerated))
+        }
+
+        /*
+            Returns the level and last throw of a ticket, without the need for a ticket proof
+        */
+        pub fn get_ticket_status(&self, ticket_id: NonFungibleLocalId) -> (i8, String) {
+            let ticket_data: Ticket = borrow_resource_manager!(self.my_non_fungible_ticket)
+                .get_non_fungible_data(&ticket_id);
+            (ticket_data.level, ticket_data.last_throw)
+        }
+
+        /*
+            Returns (steps to the win level, steps to level 0) of a ticket, e.g. for progress bars
+        */
+        pub fn ticket_margins(&self, ticket_id: NonFungibleLocalId) -> (i8, i8) {
+            let ticket_data: Ticket = borrow_resource_manager!(self.my_non_fungible_ticket)
+                .get_non_fungible_data(&ticket_id);
+            (self.win_level - ticket_data.level, ticket_data.level)
+        }
+
+        /*
+            Returns the last throws of a ticket, oldest first
+        */
+        pub fn get_recent_throws(&self, ticket_id: NonFungibleLocalId) -> Vec<String> {
+            let ticket_data: Ticket = borrow_resource_manager!(self.my_non_fungible_ticket)
+                .get_non_fungible_data(&ticket_id);
+            ticket_data.recent_throws
+        }
+
+        /*
+            Returns whether a ticket can be redeemed, it's on the win level or on a prize tier level
+        */
+        pub fn is_redeemable(&self, ticket_id: NonFungibleLocalId) -> bool {
+            let ticket_data: Ticket = borrow_resource_manager!(self.my_non_fungible_ticket)
+                .get_non_fungible_data(&ticket_id);
+            self.prize_for_level(ticket_data.level).is_some()
+        }
+
+        /*
+            Returns the last RECENT_GLOBAL_THROWS throws of all tickets, oldest first, for a live feed
+        */
+        pub fn recent_global_throws(&self) -> Vec<String> {
+            self.recent_global_throws.clone()
+        }
+
+        /*
+            Returns the play record of a ticket as (rounds played, wins, losses)
+            A round where the dice kept tying counts as neither a win or a loss.
+        */
+        pub fn get_ticket_record(&self, ticket_id: NonFungibleLocalId) -> (u32, u16, u16) {
+            let ticket_data: Ticket = borrow_resource_manager!(self.my_non_fungible_ticket)
+                .get_non_fungible_data(&ticket_id);
+            (ticket_data.rounds_played, ticket_data.wins, ticket_data.losses)
+        }
+
+        /*
+            Returns the number of rounds a ticket won in a row since its last loss or streak bonus
+        */
+        pub fn get_win_streak(&self, ticket_id: NonFungibleLocalId) -> u8 {
+            let ticket_data: Ticket = borrow_resource_manager!(self.my_non_fungible_ticket)
+                .get_non_fungible_data(&ticket_id);
+            ticket_data.win_streak
+        }
+
+        /*
+            Give a ticket a nickname of at most MAX_NICKNAME_LENGTH characters, purely cosmetic
+        */
+        pub fn set_nickname(&mut self, NFTTicket: Proof, name: String) {
+            self.assert_admin_badge_present();
+            let nft_id = self.validate_ticket_proof(NFTTicket);
+            assert!(name.chars().count() <= MAX_NICKNAME_LENGTH, 
+                "The nickname can be at most {} characters long", MAX_NICKNAME_LENGTH);
+
+            let resource_manager: &mut ResourceManager = 
+                borrow_resource_manager!(self.my_non_fungible_ticket);
+            let mut ticket_data: Ticket = resource_manager.get_non_fungible_data(&nft_id);
+            ticket_data.nickname = name;
+
+            self.authorize_update(|| resource_manager.update_non_fungible_data(
+                &nft_id, 
+                ticket_data
+            ));
+        }
+
+        /*
+            Returns the nickname of a ticket, empty when no nickname was set
+        */
+        pub fn get_nickname(&self, ticket_id: NonFungibleLocalId) -> String {
+            let ticket_data: Ticket = borrow_resource_manager!(self.my_non_fungible_ticket)
+                .get_non_fungible_data(&ticket_id);
+            ticket_data.nickname
+        }
+
+        /*
+            Returns the highest level a ticket reached while playing in the current season, 
+            None when it was not played this season
+        */
+        pub fn get_high_score(&self, ticket_id: NonFungibleLocalId) -> Option<i8> {
+            self.get_season_high_score(self.season, ticket_id)
+        }
+
+        /*
+            Returns the highest level a ticket reached in the given season, also for archived seasons
+        */
+        pub fn get_season_high_score(&self, season: u32, ticket_id: NonFungibleLocalId) -> Option<i8> {
+            self.high_scores.get(&(season, ticket_id)).map(|score| *score)
+        }
+
+        /*
+            Returns the number of the current leaderboard season, the first season is 1
+        */
+        pub fn season(&self) -> u32 {
+            self.season
+        }
+
+        /*
+            Start a new leaderboard season, the high scores of the old season are archived
+            and new high scores accumulate under the new season.
+            Admin only function.
+        */
+        pub fn reset_season(&mut self) {
+            self.season += 1;
+            Runtime::emit_event(SeasonReset { season: self.season });
+        }
+
+        /*
+            Returns the number of epochs since a ticket was minted, e.g. for loyalty bonuses
+        */
+        pub fn ticket_age(&self, ticket_id: NonFungibleLocalId) -> u64 {
+            let ticket_data: Ticket = borrow_resource_manager!(self.my_non_fungible_ticket)
+                .get_non_fungible_data(&ticket_id);
+            Runtime::current_epoch() - ticket_data.minted_epoch
+        }
+
+        /*
+            Put prize tokens in the prize vault so players can redeem their price.
+            Admin only function.
+        */
+        pub fn fund_prize_vault(&mut self, bucket: Bucket) {
+            assert!(
+                bucket.resource_address() == self.prize_vault.resource_address(),
+                "The prize vault can only be funded with the prize resource"
+            );
+            self.prize_vault.put(bucket);
+        }
+
+        /*
+            Withdrawal all coin from the main wallet, except the minimum reserve
+            Admin only function, needs a proof of two admin badges.
+        */
+        pub fn withdrawal_all(&mut self) -> Bucket {
+            let available = self.available_above_reserve();
+            let xrd_withdrawal = self.radix_vault.take(available);
+            self.vault_changed("withdrawal");
+            xrd_withdrawal
+        }
+
+        /*
+            Withdraw all coin from the main wallet like withdrawal_all, except the minimum reserve,
+            and record the withdrawn amount with a FundsWithdrawn event for auditing.
+            Admin only function, needs a proof of two admin badges.
+        */
+        pub fn withdraw_to(&mut self) -> Bucket {
+            let xrd_withdrawal = self.withdrawal_all();
+            Runtime::emit_event(FundsWithdrawn { amount: xrd_withdrawal.amount() });
+            xrd_withdrawal
+        }
+
+        /*
+            Wind down the game: pause it and return (funds, prizes, admin badge).
+            The funds are the main wallet including the minimum reserve, the jackpot and the fees
+            in the accepted buy-in, the prizes are the prize vault and redeem fees in the prize resource.
+            Without the internal admin badge no ticket can be minted, played, redeemed or burned anymore,
+            so tickets on the win level or a prize tier lose their prize. Let winners redeem before
+            shutting down.
+            Admin only function, needs a proof of two admin badges.
+        */
+        pub fn shutdown(&mut self) -> (Bucket, Bucket, Bucket) {
+            self.paused = true;
+
+            let mut xrd_withdrawal = self.radix_vault.take_all();
+            self.vault_changed("shutdown");
+            xrd_withdrawal.put(self.jackpot_vault.take_all());
+            xrd_withdrawal.put(self.fee_vault.take_all());
+            let mut prize_withdrawal = self.prize_vault.take_all();
+            prize_withdrawal.put(self.prize_fee_vault.take_all());
+
+            (xrd_withdrawal, prize_withdrawal, self.admin_vault.take_all())
+        }
+
+        /*
+            Collect all operator fees, the main wallet is not touched
+            Admin only function.
+        */
+        pub fn collect_fees(&mut self) -> Bucket {
+            self.fee_vault.take_all()
+        }
+
+        /*
+            Collect the redeem fees of prizes paid in another resource than the accepted buy-in,
+            redeem fees in the accepted buy-in go to the fee vault and are collected with collect_fees.
+            Admin only function.
+        */
+        pub fn collect_prize_fees(&mut self) -> Bucket {
+            self.prize_fee_vault.take_all()
+        }
+
+        /*
+            Withdrawal an amount of coin from the main wallet, leaving the rest as prize money
+            Admin only function, needs a proof of two admin badges.
+        */
+        pub fn withdraw(&mut self, amount: Decimal) -> Bucket {
+            self.take_above_reserve(amount, "withdrawal")
+        }
+
+        /*
+            Sweep XRD out of the main wallet for an external buyback, XRD itself can't be burned.
+            A withdrawal which is booked separately, the minimum reserve is protected the same way.
+            Admin only function, needs a proof of two admin badges.
+        */
+        pub fn sweep_to(&mut self, amount: Decimal) -> Bucket {
+            let swept = self.take_above_reserve(amount, "sweep");
+            Runtime::emit_event(SweepPerformed { amount });
+            swept
+        }
+
+        // Take XRD out of the main wallet, failing when the minimum reserve would be breached
+        fn take_above_reserve(&mut self, amount: Decimal, reason: &str) -> Bucket {
+            let available = self.available_above_reserve();
+            assert!(amount <= available, 
+                "Not enough funds in the vault, requested {} but only {} available above the reserve of {}", 
+                amount, available, self.min_reserve);
+            let xrd_withdrawal = self.radix_vault.take(amount);
+            self.vault_changed(reason);
+            xrd_withdrawal
+        }
+
+        /*
+            Withdraw the internal admin badge, e.g. to hand it over when the dApp is sold.
+            Minting, playing, redeeming and burning tickets fail until the badge is deposited again.
+            Admin only function, requires 2 admin badges like the withdrawals.
+        */
+        pub fn withdraw_admin_badge(&mut self) -> Bucket {
+            assert!(!self.admin_vault.is_empty(), "The internal admin badge is already withdrawn");
+            self.admin_vault.take_all()
+        }
+
+        /*
+            Put the internal admin badge back, so the game can mint and update tickets again.
+            Admin only function.
+        */
+        pub fn deposit_admin_badge(&mut self, badge: Bucket) {
+            assert!(
+                badge.resource_address() == self.admin_vault.resource_address(),
+                "The supplied bucket does not contain the admin badge"
+            );
+            assert!(badge.amount() == dec!("1"), "Only one (1) admin badge can be deposited");
+            assert!(self.admin_vault.is_empty(), "The internal admin badge is already present");
+            self.admin_vault.put(badge);
+        }
+
+        /*
+            Withdraw the internal minter and updater badges as (minter, updater), e.g. to serve the
+            tickets with only minting rights from another component with instantiate_with_resource.
+            Minting, playing, redeeming and burning tickets fail until the badges are deposited again.
+            Admin only function, requires 2 admin badges like the withdrawals.
+        */
+        pub fn withdraw_ticket_badges(&mut self) -> (Bucket, Bucket) {
+            match (&mut self.minter_vault, &mut self.updater_vault) {
+                (Some(minter_vault), Some(updater_vault)) => {
+                    assert!(!minter_vault.is_empty(), "The ticket badges are already withdrawn");
+                    (minter_vault.take_all(), updater_vault.take_all())
+                }
+                _ => panic!("This component has no separate ticket badges"),
+            }
+        }
+
+        /*
+            Put the internal minter and updater badges back, so the game can mint and update tickets again.
+            Admin only function.
+        */
+        pub fn deposit_ticket_badges(&mut self, minter_badge: Bucket, updater_badge: Bucket) {
+            match (&mut self.minter_vault, &mut self.updater_vault) {
+                (Some(minter_vault), Some(updater_vault)) => {
+                    assert!(
+                        minter_badge.resource_address() == minter_vault.resource_address() 
+                            && updater_badge.resource_address() == updater_vault.resource_address(),
+                        "The supplied buckets do not contain the minter and updater badges"
+                    );
+                    assert!(minter_badge.amount() == dec!("1") && updater_badge.amount() == dec!("1"), 
+                        "Only one (1) minter and one (1) updater badge can be deposited");
+                    assert!(minter_vault.is_empty(), "The ticket badges are already present");
+                    minter_vault.put(minter_badge);
+                    updater_vault.put(updater_badge);
+                }
+                _ => panic!("This component has no separate ticket badges"),
+            }
+        }
+
+        /*
+            Set the amount of XRD that always stays in the main wallet to keep prizes and refunds liquid,
+            withdraw and withdrawal_all can't go below it. 0 by default.
+            Admin only function.
+        */
+        pub fn set_min_reserve(&mut self, min_reserve: Decimal) {
+            assert!(min_reserve >= dec!("0"), "The minimum reserve can not be negative");
+            self.min_reserve = min_reserve;
+        }
+
+        // XRD in the main wallet which can be withdrawn without breaching the minimum reserve
+        fn available_above_reserve(&self) -> Decimal {
+            if self.radix_vault.amount() > self.min_reserve {
+                self.radix_vault.amount() - self.min_reserve
+            } else {
+                dec!("0")
+            }
+        }
+
+        /*
+            Pause or unpause the game, e.g. during maintenance.
+            Prizes can still be redeemed and tickets burned while the game is paused.
+            Admin only function.
+        */
+        pub fn set_paused(&mut self, paused: bool) {
+            self.paused = paused;
+        }
+
+        /*
+            Returns whether the game is paused
+        */
+        pub fn is_paused(&self) -> bool {
+            self.paused
+        }
+
+        /*
+            Set the number of epochs a ticket has to wait between rounds, to discourage bot spamming
+            0 disables the cooldown.
+            Admin only function.
+        */
+        pub fn set_cooldown_epochs(&mut self, cooldown_epochs: u64) {
+            self.cooldown_epochs = cooldown_epochs;
+        }
+
+        /*
+            Set the largest level change of a single round, so dice with many faces or a staked
+            multiplier can't swing a ticket wildly. 0 disables the limit.
+            Admin only function.
+        */
+        pub fn set_max_delta(&mut self, max_delta: i8) {
+            assert!(max_delta >= 0, "The max delta can not be negative");
+            self.max_delta = max_delta;
+        }
+
+        /*
+            Set the number of epochs between two prize redemptions, 0 disables the redeem cooldown.
+            The cooldown is global instead of per ticket, so a player holding many winning tickets
+            can't drain the prize vault in one go and leave nothing for other winners.
+            Admin only function.
+        */
+        pub fn set_redeem_cooldown_epochs(&mut self, redeem_cooldown_epochs: u64) {
+            self.redeem_cooldown_epochs = redeem_cooldown_epochs;
+        }
+
+        /*
+            Run a double prize promo: prizes redeemed before the given epoch pay twice the amount,
+            when the prize vault can't afford the double the single prize is paid. 0 ends the promo.
+            Admin only function.
+        */
+        pub fn set_double_prize_until_epoch(&mut self, epoch: u64) {
+            self.double_prize_until_epoch = epoch;
+        }
+
+        /*
+            Set the amount of XRD refunded for a level 0 ticket, 0 by default
+            At most the ticket price, rounded down to the divisibility of the accepted buy-in.
+            Admin only function.
+        */
+        pub fn set_dead_ticket_refund(&mut self, refund: Decimal) {
+            assert!(refund >= dec!("0") && refund <= self.ticket_price, 
+                "The dead ticket refund must be between 0 and the ticket price {}", self.ticket_price);
+            self.dead_ticket_refund = self.round_to_divisibility(refund, RoundingMode::TowardsZero);
+        }
+
+        /*
+            Set the part of the ticket price accrued for the referrer of a referred sale, 0 by default
+            Admin only function.
+        */
+        pub fn set_referral_reward(&mut self, reward: Decimal) {
+            assert!(reward >= dec!("0") && reward <= self.buyin_price(self.ticket_price), 
+                "The referral reward must be between 0 and the ticket price {}", self.ticket_price);
+            self.referral_reward = self.round_to_divisibility(reward, RoundingMode::TowardsZero);
+        }
+
+        /*
+            Set the number of won rounds in a row which grant one extra level, 0 disables the bonus
+            Admin only function.
+        */
+        pub fn set_streak_bonus_threshold(&mut self, threshold: u8) {
+            self.streak_bonus_threshold = threshold;
+        }
+
+        /*
+            Set the amount of XRD set aside for a ticket dropping to level 0, 0 by default
+            Rounded down to the divisibility of the accepted buy-in.
+            Admin only function.
+        */
+        pub fn set_bust_consolation(&mut self, consolation: Decimal) {
+            assert!(consolation >= dec!("0"), "The bust consolation can not be negative");
+            self.bust_consolation = self.round_to_divisibility(consolation, RoundingMode::TowardsZero);
+        }
+
+        /*
+            Set the part of every ticket sale that goes to the fee vault, 0 by default
+            Admin only function.
+        */
+        pub fn set_fee_fraction(&mut self, fraction: Decimal) {
+            assert!(fraction >= dec!("0") && fraction + self.jackpot_fraction <= dec!("1"), 
+                "The fee fraction must be between 0 and {}", dec!("1") - self.jackpot_fraction);
+            self.fee_fraction = fraction;
+        }
+
+        /*
+            Set the part of every redeemed prize kept as operator fee, 0 by default
+            The jackpot won on the win level is paid in full.
+            Admin only function.
+        */
+        pub fn set_redeem_fee_fraction(&mut self, fraction: Decimal) {
+            assert!(fraction >= dec!("0") && fraction <= dec!("1"), 
+                "The redeem fee fraction must be between 0 and 1, got {}", fraction);
+            self.redeem_fee_fraction = fraction;
+        }
+
+        /*
+            Set the level of a ticket, e.g. to compensate a player after an outage
+            The win level and prize tiers can only be reached by playing, see assert_adjustable_level.
+            Admin only function.
+        */
+        pub fn set_ticket_level(&mut self, ticket_id: NonFungibleLocalId, level: i8) {
+            self.assert_admin_badge_present();
+            self.assert_adjustable_level(level);
+
+            let resource_manager: &mut ResourceManager = 
+                borrow_resource_manager!(self.my_non_fungible_ticket);
+            let mut ticket_data: Ticket = resource_manager.get_non_fungible_data(&ticket_id);
+
+            self.track_max_level(ticket_data.level, level);
+            ticket_data.level = level;
+            ticket_data.last_throw = "Adjusted by admin".to_string();
+
+            self.authorize_update(|| resource_manager.update_non_fungible_data(
+                &ticket_id, 
+                ticket_data
+            ));
+
+            Runtime::emit_event(LevelAdjusted { ticket_id, new_level: level });
+        }
+
+        /*
+            Set the name, icon url and description in the component metadata, so wallets can show the dApp
+            Admin only function.
+        */
+        pub fn set_branding(&mut self, name: String, icon_url: String, description: String) {
+            let component_address: ComponentAddress = 
+                Runtime::actor().component_address().unwrap();
+            let component = borrow_component!(component_address);
+            component.set_metadata("name", name);
+            component.set_metadata("icon_url", icon_url);
+            component.set_metadata("description", description);
+        }
+
+        fn assert_ticket_price(price: Decimal) {
+            assert!(price >= min_ticket_price() && price <= max_ticket_price(), 
+                "The ticket price must be between {} and {}, got {}", 
+                min_ticket_price(), max_ticket_price(), price);
+        }
+
+        /*
+            Set the price of a new ticket, renewals and stakes follow the new price
+            The referral reward has to stay within the new price.
+            Admin only function.
+        */
+        pub fn set_ticket_price(&mut self, price: Decimal) {
+            Self::assert_ticket_price(price);
+            assert!(self.referral_reward <= self.buyin_price(price), 
+                "The ticket price can not be below the referral reward {}", self.referral_reward);
+            self.ticket_price = price;
+        }
+
+        fn assert_reinit_discount(factor: Decimal) {
+            assert!(factor > dec!("0") && factor <= dec!("1"), 
+                "The reinit discount must be greater than 0 and at most 1, got {}", factor);
+        }
+
+        /*
+            Set the prize paid for a ticket on the win level
+            The new amount applies to every following redemption, also for tickets already on the win level,
+            the PrizeAmountChanged event warns players before they redeem.
+            Admin only function.
+        */
+        pub fn set_prize_amount(&mut self, amount: Decimal) {
+            assert!(amount >= dec!("0"), "The prize amount can not be negative");
+            let old = self.prize_amount;
+            self.prize_amount = amount;
+            Runtime::emit_event(PrizeAmountChanged { old, new: amount });
+        }
+
+        /*
+            Set the part of the ticket price to pay for reinitializing a ticket, 0.9 means a 10% discount
+            Admin only function.
+        */
+        pub fn set_reinit_discount(&mut self, factor: Decimal) {
+            Self::assert_reinit_discount(factor);
+            self.reinit_discount = factor;
+        }
+
+        /*
+            Reinitialize a ticket that has NFT level field set to 0
+            Using this method gives a discount compared to buying new ticket, 10% by default.
+        */
+        pub fn reinit_ticket(&mut self, NFTTicket: Proof, mut buyin: Bucket) -> Bucket {
+
+            assert!(!self.paused, "Game is paused");
+            self.assert_admin_badge_present();
+            assert!(!buyin.is_empty(), "The supplied bucket is empty");
+
+            assert!(
+                buyin.resource_address() == self.accepted_buyin,
+                "The Buy-in can only be done with the accepted buy-in resource"
+            );
+            // the reinit price is the ticket price with the reinit discount applied
+            self.assert_level_game();
+            let amount: Decimal = self.buyin_price(self.ticket_price * self.reinit_discount);
+            assert!(buyin.amount() >= amount, 
+                "Not enough XRD supplied, reinitializing costs {} but {} was supplied", 
+                amount, buyin.amount());
+
+            let nft_id = self.validate_ticket_proof(NFTTicket);
+        
+            let resource_manager: &mut ResourceManager = 
+                borrow_resource_manager!(self.my_non_fungible_ticket);
+        
+            let mut ticket_data: Ticket = resource_manager.get_non_fungible_data(&nft_id);
+
+            assert!(ticket_data.level == 0, "Level not 0, Ticket still playable");
+
+            let xrd_buy_in = buyin.take(amount);
+            self.total_buyins += amount;
+            self.radix_vault.put(xrd_buy_in);
+            self.vault_changed("reinit");
+
+            ticket_data.level = self.start_level;
+            ticket_data.last_throw = "Just reinitialized the Ticket".to_string();
+            ticket_data.recent_throws.clear();
+            ticket_data.rounds_played = 0;
+            ticket_data.wins = 0;
+            ticket_data.losses = 0;
+            ticket_data.last_played_epoch = 0;
+            ticket_data.win_streak = 0;
+            
+            self.authorize_update(|| resource_manager.update_non_fungible_data(
+                &nft_id, 
+                ticket_data
+            ));
+
+            Runtime::emit_event(TicketReinitialized { ticket_id: nft_id });
+
+            buyin
+        }
+
+        /*
+            Admin can retreive a free ticket 
+            Admin only function
+            function is also called by the buy_token function.
+        */
+        pub fn admin_ticket(&mut self) -> Bucket {
+            self.mint_ticket(self.start_level, "New Ticket, no play history")
+        }
+
+        /*
+            Admin can mint a ticket on any level between 0 and the win level, for giveaways and promos
+            The win level and prize tiers are excluded, see assert_adjustable_level.
+            Admin only function
+        */
+        pub fn mint_promo_ticket(&mut self, level: i8) -> Bucket {
+            self.assert_adjustable_level(level);
+            self.mint_ticket(level, "Promo ticket")
+        }
+
+        /*
+            A single admin badge can set or mint tickets on any level below the win level except the
+            prize tiers, otherwise it could redeem the prize vault, which takes two admin badges.
+        */
+        fn assert_adjustable_level(&self, level: i8) {
+            assert!(level >= 0 && level < self.win_level, 
+                "The level must be between 0 and below the win level {}, got {}", self.win_level, level);
+            assert!(self.prize_for_level(level).is_none(), 
+                "Level {} is a prize tier, only reached by playing", level);
+        }
+
+        /*
+            Admin can mint a batch of promo tickets on the same level into one bucket, for airdrops
+            Admin only function
+        */
+        pub fn mint_promo_batch(&mut self, count: u32, level: i8) -> Bucket {
+            assert!(count > 0, "At least one (1) promo ticket must be minted");
+            let mut NFT_bucket = self.mint_promo_ticket(level);
+            for _ in 1..count {
+                NFT_bucket.put(self.mint_promo_ticket(level));
+            }
+            NFT_bucket
+        }
+
+        /*
+            Admin can mint a ticket on the start level as a gift, the message is shown as its last throw
+            Admin only function
+        */
+        pub fn gift_ticket(&mut self, message: String) -> Bucket {
+            assert!(!message.is_empty(), "The gift message can not be empty");
+            self.mint_ticket(self.start_level, &message)
+        }
+
+        /*
+            Mint a new ticket on the given level, the cap on the number of tickets is checked here
+        */
+        fn mint_ticket(&mut self, level: i8, last_throw: &str) -> Bucket {
+
+            self.assert_admin_badge_present();
+            self.assert_level_game();
+            assert!(self.max_tickets == 0 || self.nrNFTsgenerated < self.max_tickets, 
+                "All {} tickets have been minted, no more tickets available", self.max_tickets);
+            self.track_max_level(0, level);
+          
+            let NFT_data = Ticket {
+                level,
+                last_throw: last_throw.to_string(),
+                recent_throws: Vec::new(),
+                rounds_played: 0,
+                wins: 0,
+                losses: 0,
+                last_played_epoch: 0,
+                minted_epoch: Runtime::current_epoch(),
+                nickname: String::new(),
+                win_streak: 0,
+            };
+
+            self.nrNFTsgenerated = self.nrNFTsgenerated.wrapping_add(1u64);
+
+            let nft_id = NonFungibleLocalId::Integer(self.nrNFTsgenerated.into());
+            let transaction = Runtime::transaction_hash();
+            if transaction != self.minting_transaction {
+                self.minting_transaction = transaction;
+                self.minted_in_transaction.clear();
+            }
+            self.minted_in_transaction.push(nft_id.clone());
 
-            // Return the instantiated component and the admin badge that was just minted
-            (component, my_admin_badge)
+            let NFT_bucket = self.authorize_mint(||{
+                borrow_resource_manager!(self.my_non_fungible_ticket).mint_non_fungible(
+                &nft_id,
+                NFT_data
+                )
+            });
+
+            Runtime::emit_event(TicketMinted { ticket_id: nft_id });
+
+            NFT_bucket
         }
 
         /*
-            Die roll function, used internally
-            This function is blocked for external call by access ruls
+            Buy one RaDiceX ticket for the ticket price, mint a NFT and send back
+            Returns (ticket NFT, change of the buy-in), a TicketPurchased event holds the new ticket id.
         */
-        pub fn roll_dice(&mut self) -> i8 {
-            let random = Runtime::generate_uuid();
-            let dieval:i8 = ((random % 6) + 1) as i8;
-            dieval
+        pub fn buy_ticket(&mut self, buyin: Bucket) -> (Bucket, Bucket) {
+            self.sell_ticket(buyin, None)
         }
 
         /*
-            Die roll function, external available for comparison
+            Buy a ticket on referral of the owner of an existing ticket,
+            the referral reward is taken from the ticket price and accrued for the referrer ticket.
+            A ticket bought in the same transaction can't be the referrer, the buyer would refer themself.
+            Returns (ticket NFT, change of the buy-in)
         */
-        pub fn roll_dice_old(&mut self) -> i8 {
-            let random = Runtime::generate_uuid();
-            let dieval:i8 = ((random % 6) + 1) as i8;
-            dieval
+        pub fn buy_ticket_referred(&mut self, buyin: Bucket, referrer: NonFungibleLocalId) 
+            -> (Bucket, Bucket) {
+            let resource_manager: &mut ResourceManager = 
+                borrow_resource_manager!(self.my_non_fungible_ticket);
+            assert!(resource_manager.non_fungible_exists(&referrer), 
+                "The referrer ticket {} does not exist", referrer);
+            assert!(!(self.minting_transaction == Runtime::transaction_hash() 
+                && self.minted_in_transaction.contains(&referrer)), 
+                "The referrer ticket {} can not be bought in the same transaction", referrer);
+            self.sell_ticket(buyin, Some(referrer))
         }
+
         /*
-            Alterniative Die roll function, used internally, but should be external accessable
-            The modulo (%) function is pratically a division, 
-            this routine could be "cheaper" in network execution
+            Claim the referral rewards accrued for a ticket
         */
-        pub fn roll_dice_alt(&mut self) -> i8 {
-            loop{
-                let mut random:u128 = Runtime::generate_uuid();
-                while random > 0{
-                    let myval = random & 0x7;
-                    // if 0x6 or 0x7 throw away result and redo check on 3 new bits.
-                    if myval < 0x6{ 
-                        return (myval+1) as i8 ;
-                    }
-                    // get 3 new bits but shift 4 because 128/3 != integer
-                    random = random >> 4; 
-                }
-            }
+        pub fn claim_referral(&mut self, NFTTicket: Proof) -> Bucket {
+            let nft_id = self.validate_ticket_proof(NFTTicket);
+            let accrued = self.get_referral_balance(nft_id.clone());
+            assert!(accrued > dec!("0"), "No referral rewards to claim for this ticket");
+            self.referral_balances.insert(nft_id, dec!("0"));
+            self.referral_vault.take(accrued)
         }
 
         /*
-            Deposit x coins in the main wallet so players can redeem their price.
+            Returns the unclaimed referral rewards of a ticket
         */
-        pub fn deposit(&mut self, amount: Decimal, mut deposit: Bucket) -> Bucket {
+        pub fn get_referral_balance(&self, nft_id: NonFungibleLocalId) -> Decimal {
+            self.referral_balances.get(&nft_id).map_or(dec!("0"), |amount| *amount)
+        }
 
+        // Sell one ticket, crediting the referral reward to the referrer ticket if there is one
+        fn sell_ticket(&mut self, mut buyin: Bucket, referrer: Option<NonFungibleLocalId>) 
+            -> (Bucket, Bucket) {
+
+            assert!(!self.paused, "Game is paused");
+            assert!(!buyin.is_empty(), "The supplied bucket is empty");
+
+            // check if the buy-in bucket is XRD type, and hold enough coin
             assert!(
-                deposit.resource_address() == self.radix_vault.resource_address(),
-                "The Buy-in can only be done with Radix tokens"
+                buyin.resource_address() == self.accepted_buyin,
+                "The Buy-in can only be done with the accepted buy-in resource"
             );
-            assert!(deposit.amount()>amount, "There are not enough tokens in your account");
+            let amount: Decimal = self.buyin_price(self.ticket_price);
+            assert!(!(buyin.amount()<amount), "Not enough XRD supplied");
+ 
+            let NFT_bucket = self.admin_ticket();
 
-            let xrd_deposit = deposit.take(amount);
-            self.radix_vault.put(xrd_deposit);
+            let mut sale = buyin.take(amount);
+            if let Some(referrer) = referrer {
+                let accrued = self.get_referral_balance(referrer.clone());
+                self.referral_vault.put(sale.take(self.referral_reward));
+                self.referral_balances.insert(referrer, accrued + self.referral_reward);
+                // the reward is part of the buy-in, collect_ticket_sale only counts the rest
+                self.total_buyins += self.referral_reward;
+            }
+            self.collect_ticket_sale(sale);
 
-            // return the bucket incase of a surplus of tokens
-            deposit
+            Runtime::emit_event(TicketPurchased {
+                ticket_id: NFT_bucket.non_fungible_local_id(),
+                price_paid: amount,
+            });
+ 
+            (NFT_bucket, buyin)
         }
 
         /*
-            Withdrawal all coin from the main wallet 
-            Admin only function.
+            Buy a ticket and play its first round right away, saving new players a transaction.
+            Returns (ticket NFT, change of the buy-in, level after the first round)
         */
-        pub fn withdrawal_all(&mut self) -> Bucket {
-            let xrd_withdrawal = self.radix_vault.take_all();
-            xrd_withdrawal
+        pub fn buy_and_play(&mut self, buyin: Bucket) -> (Bucket, Bucket, i8) {
+            let (NFT_bucket, change) = self.buy_ticket(buyin);
+            // holding the freshly minted ticket is proof enough, no ticket proof is needed
+            let newlevel = self.play_ticket(NFT_bucket.non_fungible_local_id(), 1);
+            (NFT_bucket, change, newlevel)
         }
 
         /*
-            Reinitialize a ticket that has NFT level field set to 0
-            Using this method gives a 10% discount compared to buying new ticket.
+            Buy a number of RaDiceX tickets in one call, all NFTs are send back in one bucket
+            together with the remaining XRD as (ticket NFTs, change of the buy-in)
         */
-        pub fn reinit_ticket(&mut self, NFTTicket: Proof, mut buyin: Bucket) -> Bucket {
+        pub fn buy_tickets(&mut self, count: u32, mut buyin: Bucket) -> (Bucket, Bucket) {
 
+            assert!(!self.paused, "Game is paused");
+            assert!(count > 0, "At least one (1) ticket must be bought");
+
+            // check if the buy-in bucket is XRD type, and hold enough coin for all tickets
             assert!(
-                buyin.resource_address() == self.radix_vault.resource_address(),
-                "The Buy-in can only be done with Radix tokens"
+                buyin.resource_address() == self.accepted_buyin,
+                "The Buy-in can only be done with the accepted buy-in resource"
             );
-            assert!(!(buyin.amount()<dec!("0.9")), "Not enough XRD supplied");
-            assert!(NFTTicket.amount()==dec!("1"), "Only one (1) ticket per call is supported");
+            // every ticket costs the rounded price of a single ticket, as reported in its event
+            let price: Decimal = self.buyin_price(self.ticket_price);
+            let amount: Decimal = price * count;
+            assert!(!(buyin.amount()<amount), "Not enough XRD supplied");
 
-            let validated_proof = NFTTicket.validate_proof(
-                ProofValidationMode::ValidateResourceAddress(self.my_non_fungible_ticket)
-            ).expect("invalid proof");
-
-            let nft_id = validated_proof.non_fungible_local_id();
-        
-            let resource_manager: &mut ResourceManager = 
-                borrow_resource_manager!(self.my_non_fungible_ticket);
-        
-            let mut ticket_data: Ticket = resource_manager.get_non_fungible_data(&nft_id);
+            let mut NFT_bucket = self.admin_ticket();
+            for _ in 1..count {
+                NFT_bucket.put(self.admin_ticket());
+            }
+            for ticket_id in NFT_bucket.non_fungible_local_ids() {
+                Runtime::emit_event(TicketPurchased {
+                    ticket_id,
+                    price_paid: price,
+                });
+            }
 
-            assert!(ticket_data.level == 0, "Level not 0, Ticket still playable");
+            self.collect_ticket_sale(buyin.take(amount));
 
-            let amount: Decimal = dec!("0.9");
+            (NFT_bucket, buyin)
+        }
 
-            let xrd_buy_in = buyin.take(amount);
+        /*
+            Split the XRD of a ticket sale between the jackpot, the fee vault and the main wallet
+        */
+        fn collect_ticket_sale(&mut self, mut xrd_buy_in: Bucket) {
+            self.total_buyins += xrd_buy_in.amount();
+            // the shares are rounded down, the dust stays in the main wallet
+            let jackpot_share = self.round_to_divisibility(
+                xrd_buy_in.amount() * self.jackpot_fraction, RoundingMode::TowardsZero);
+            let fee_share = self.round_to_divisibility(
+                xrd_buy_in.amount() * self.fee_fraction, RoundingMode::TowardsZero);
+            self.jackpot_vault.put(xrd_buy_in.take(jackpot_share));
+            self.fee_vault.put(xrd_buy_in.take(fee_share));
             self.radix_vault.put(xrd_buy_in);
+            self.vault_changed("ticket_sale");
+        }
 
-            ticket_data.level = 10;
-            ticket_data.last_throw = "Just reinitialized the Ticket".to_string();
-            
-            self.admin_vault.authorize(|| resource_manager.update_non_fungible_data(
-                &nft_id, 
-                ticket_data
-            ));
+        /*
+            Price in the buy-in resource, rounded up to its divisibility.
+            A price with more decimals than the resource supports can't be taken from a bucket.
+        */
+        fn buyin_price(&self, price: Decimal) -> Decimal {
+            self.round_to_divisibility(price, RoundingMode::TowardsPositiveInfinity)
+        }
 
-            buyin
+        // Round an amount of the buy-in resource to the number of decimals the resource supports
+        fn round_to_divisibility(&self, amount: Decimal, mode: RoundingMode) -> Decimal {
+            Self::round_for_resource(self.accepted_buyin, amount, mode)
+        }
+
+        // Round an amount of any fungible resource to the number of decimals the resource supports
+        fn round_for_resource(resource: ResourceAddress, amount: Decimal, mode: RoundingMode) -> Decimal {
+            match borrow_resource_manager!(resource).resource_type() {
+                ResourceType::Fungible { divisibility } => amount.round(divisibility, mode),
+                _ => amount.round(0, mode),
+            }
         }
 
         /*
-            Admin can retreive a free ticket 
-            Admin only function
-            function is also called by the buy_token function.
+            Run f with the badge which mints tickets,
+            the internal admin badge without a minter badge.
+            Gives a clear error when the admin badge was withdrawn with withdraw_admin_badge.
         */
-        pub fn admin_ticket(&mut self) -> Bucket {
-          
-            let NFT_data = Ticket {
-                level: 10,
-                last_throw: "New Ticket, no play history".to_string(),
-            };
+        fn authorize_mint<F: FnOnce() -> O, O>(&self, f: F) -> O {
+            self.authorize_with(&self.minter_vault, f)
+        }
 
-            self.nrNFTsgenerated = self.nrNFTsgenerated.wrapping_add(1u64);
+        /*
+            Run f with the badge which updates and burns tickets,
+            the internal admin badge without an updater badge.
+            Gives a clear error when the admin badge was withdrawn with withdraw_admin_badge.
+        */
+        fn authorize_update<F: FnOnce() -> O, O>(&self, f: F) -> O {
+            self.authorize_with(&self.updater_vault, f)
+        }
 
-            let NFT_bucket = self.admin_vault.authorize(||{
-                borrow_resource_manager!(self.my_non_fungible_ticket).mint_non_fungible(
-                &NonFungibleLocalId::Integer(self.nrNFTsgenerated.into()),
-                NFT_data
-                )
-            });
+        // Withdrawing the admin badge hands the dApp over, so the game stops even with separate badges
+        fn authorize_with<F: FnOnce() -> O, O>(&self, badge_vault: &Option<Vault>, f: F) -> O {
+            self.assert_admin_badge_present();
+            match badge_vault {
+                Some(vault) => {
+                    assert!(!vault.is_empty(), 
+                        "The ticket badges are withdrawn, internal operations are disabled until deposited");
+                    vault.authorize(f)
+                }
+                None => self.admin_vault.authorize(f),
+            }
+        }
 
-            NFT_bucket
+        /*
+            Fail early with a clear message when the internal admin badge is withdrawn,
+            called at the start of every method minting, updating or burning tickets.
+        */
+        fn assert_admin_badge_present(&self) {
+            assert!(!self.admin_vault.is_empty(), 
+                "The internal admin badge is withdrawn, internal operations are disabled until it is deposited");
         }
 
         /*
-            Buy one RaDiceX ticket for 1 XRD, mint a NFT and send back
+            Tickets and their levels are only used when chasing the win level, a fixed-odds game
+            is played with play_instant only.
         */
-        pub fn buy_ticket(&mut self, mut buyin: Bucket) -> (Bucket, Bucket) {
+        fn assert_level_game(&self) {
+            assert!(self.payout_table.is_empty(), 
+                "This is a fixed-odds game without tickets, play with play_instant");
+        }
 
-            // check if the buy-in bucket is XRD type, and hold enough coin
-            assert!(
-                buyin.resource_address() == self.radix_vault.resource_address(),
-                "The Buy-in can only be done with Radix tokens"
-            );
-            assert!(!(buyin.amount()<dec!("1")), "Not enough XRD supplied");
-            
-            let amount: Decimal = dec!("1");
- 
-            let NFT_bucket = self.admin_ticket();
+        /*
+            Keep tickets_at_max up to date when a ticket moves from old_level to new_level.
+            Minting counts as coming from level 0, burning and redeeming as going to level 0.
+            Saturates at 0, a component from instantiate_with_resource doesn't know how many
+            inherited tickets are on the win level.
+        */
+        fn track_max_level(&mut self, old_level: i8, new_level: i8) {
+            if old_level != self.win_level && new_level == self.win_level {
+                self.tickets_at_max += 1;
+            } else if old_level == self.win_level && new_level != self.win_level {
+                self.tickets_at_max = self.tickets_at_max.saturating_sub(1);
+            }
+        }
 
-            let xrd_buy_in = buyin.take(amount);
-            self.radix_vault.put(xrd_buy_in);
- 
-            (NFT_bucket, buyin)
+        /*
+            Emit a VaultChanged event with the new balance of the main wallet
+        */
+        fn vault_changed(&self, reason: &str) {
+            Runtime::emit_event(VaultChanged {
+                new_balance: self.radix_vault.amount(),
+                reason: reason.to_string(),
+            });
         }
 
         /*
-            redeem a price if the NFT level field is equal to 25
+            Prize for a ticket on the given level, the win level pays the prize amount
+            and the levels of the prize tiers pay the tier's amount.
         */
-        pub fn redeem_prize(&mut self, NFTTicket: Proof) -> Bucket {
+        fn prize_for_level(&self, level: i8) -> Option<Decimal> {
+            if level == self.win_level {
+                return Some(self.prize_amount);
+            }
+            self.prize_tiers.iter()
+                .find(|(tier_level, _)| *tier_level == level)
+                .map(|(_, amount)| *amount)
+        }
 
-            let redeem_amount: Decimal = dec!("5");
-            assert!(&redeem_amount <= &self.radix_vault.amount(), 
-                "Not enough funds in the vault to pay prize money");
-            assert!(NFTTicket.amount()==dec!("1"), "Only one (1) ticket per call is supported");
+        /*
+            redeem a price if the NFT level field is equal to the win level or one of the prize tiers
+            the redeem fee goes to the fee vault, or the prize fee vault when prizes aren't paid in the
+            accepted buy-in, the rest of the prize is paid out
+            a ticket on the win level also wins the complete jackpot
+            the ticket level is reset to 0 afterwards
+            Returns (prize, jackpot), the jackpot bucket is empty when no jackpot was won.
+        */
+        pub fn redeem_prize(&mut self, NFTTicket: Proof) -> (Bucket, Bucket) {
 
-            let validated_proof = NFTTicket.validate_proof(
-                ProofValidationMode::ValidateResourceAddress(self.my_non_fungible_ticket)
-            ).expect("invalid proof");
+            self.assert_admin_badge_present();
+            let nft_id = self.validate_ticket_proof(NFTTicket);
 
-            let nft_id = validated_proof.non_fungible_local_id();
+            let current_epoch = Runtime::current_epoch();
+            if let Some(last_redeem_epoch) = self.last_redeem_epoch {
+                assert!(current_epoch >= last_redeem_epoch + self.redeem_cooldown_epochs,
+                    "Prizes are cooling down, the next prize can be redeemed in epoch {}", 
+                    last_redeem_epoch + self.redeem_cooldown_epochs);
+            }
         
             let resource_manager: &mut ResourceManager = 
                 borrow_resource_manager!(self.my_non_fungible_ticket);
         
             let mut ticket_data: Ticket = resource_manager.get_non_fungible_data(&nft_id);
 
-            assert!(ticket_data.level == 25, "Level not 25, Ticket not redeemable");
+            let redeem_amount: Decimal = self.prize_for_level(ticket_data.level)
+                .unwrap_or_else(|| panic!("Level {} does not match a prize tier, Ticket not redeemable", 
+                    ticket_data.level));
+            if redeem_amount > self.prize_vault.amount() {
+                // alert the operator to top up the vault before failing the redemption
+                Runtime::emit_event(PrizeUnderfunded {
+                    ticket_id: nft_id,
+                    required: redeem_amount,
+                    available: self.prize_vault.amount(),
+                });
+                panic!("Not enough funds in the vault to pay prize money");
+            }
+            let doubled = current_epoch < self.double_prize_until_epoch 
+                && redeem_amount * 2 <= self.prize_vault.amount();
+            let redeem_amount = if doubled { redeem_amount * 2 } else { redeem_amount };
+
+            let won_jackpot = ticket_data.level == self.win_level;
 
+            ticket_data.last_throw = format!("Just redeemed a level {} Ticket", ticket_data.level);
+            self.track_max_level(ticket_data.level, 0);
             ticket_data.level = 0;
-            ticket_data.last_throw = "Just redeemed a level 25 Ticket".to_string();
             
-            self.admin_vault.authorize(|| resource_manager.update_non_fungible_data(
+            self.authorize_update(|| resource_manager.update_non_fungible_data(
                 &nft_id, 
                 ticket_data
             ));
 
-            let xrd_withdrawal =  self.radix_vault.take(redeem_amount);
+            let fee = Self::round_for_resource(self.prize_vault.resource_address(), 
+                redeem_amount * self.redeem_fee_fraction, RoundingMode::TowardsZero);
+            let net = redeem_amount - fee;
+            Runtime::emit_event(PrizeRedeemed {
+                ticket_id: nft_id,
+                gross: redeem_amount,
+                fee,
+                net,
+                doubled,
+            });
+
+            let fee_bucket = self.prize_vault.take(fee);
+            if fee_bucket.resource_address() == self.accepted_buyin {
+                self.fee_vault.put(fee_bucket);
+            } else {
+                self.prize_fee_vault.put(fee_bucket);
+            }
+            let prize_withdrawal = self.prize_vault.take(net);
+            self.total_prizes_paid += net;
+            self.total_wins += 1;
+            self.last_redeem_epoch = Some(current_epoch);
+            let jackpot_withdrawal = if won_jackpot {
+                self.jackpot_vault.take_all()
+            } else {
+                self.jackpot_vault.take(dec!("0"))
+            };
 
-            xrd_withdrawal
+            (prize_withdrawal, jackpot_withdrawal)
         }
 
         /*
-            Play a round of RadiceX. Two dice are rolled
-            the diff between the player value and house value is calculated and added to the NFT level field.
-            If the player reaches level 25, this token can be redeemed for 5 XRD
-            If the player reaches level 0, this ticket is no longer playable.
+            Validate that the proof holds exactly one ticket of this game and return its id
         */
-        pub fn play_round(&mut self, NFTTicket: Proof) {
-
+        fn validate_ticket_proof(&self, NFTTicket: Proof) -> NonFungibleLocalId {
             assert!(NFTTicket.amount()==dec!("1"), "Only one (1) ticket per call is supported");
 
             let validated_proof = NFTTicket.validate_proof(
                 ProofValidationMode::ValidateResourceAddress(self.my_non_fungible_ticket)
-            ).expect("invalid proof");
+            ).expect("Invalid proof, the proof is not a RaDiceX ticket");
+
+            validated_proof.non_fungible_local_id()
+        }
+
+        /*
+            Roll the dice for a ticket, the level delta is multiplied by the stake multiplier.
+            The new level is clamped between 0 and the win level, stored in the NFT and returned.
+        */
+        fn play_ticket(&mut self, nft_id: NonFungibleLocalId, multiplier: i8) -> i8 {
+            let round = self.throw_round();
+            self.apply_round(nft_id, round, multiplier)
+        }
+
+        /*
+            Apply a thrown round (house die, player die, level delta) to a ticket,
+            so a team of tickets can share the same throw.
+        */
+        fn apply_round(&mut self, nft_id: NonFungibleLocalId, round: (i8, i8, i8), multiplier: i8) 
+            -> i8 {
+
+            self.assert_admin_badge_present();
+            self.assert_level_game();
 
-            let nft_id = validated_proof.non_fungible_local_id();
-        
             let resource_manager: &mut ResourceManager = 
                 borrow_resource_manager!(self.my_non_fungible_ticket);
         
             let mut ticket_data: Ticket = resource_manager.get_non_fungible_data(&nft_id);
 
-            assert!(ticket_data.level != 25, "Ticket Level = 25, Ticket not playable");
-            assert!(ticket_data.level != 0, "Ticket Level = 0, Ticket not playable");
+            // a maxed or busted ticket is finished, the clamp below keeps levels within 0 and the win level
+            assert!(ticket_data.level != self.win_level, 
+                "Ticket Level = {} is the win level, Ticket not playable, redeem the prize instead", 
+                ticket_data.level);
+            assert!(ticket_data.level != 0, 
+                "Ticket Level = 0, Ticket not playable, reinitialize the ticket instead");
 
-            let house_die = self.roll_dice();
-            let player_die = self.roll_dice();
-            let mut diff_of_dice = &player_die - &house_die;
-            // code in case both house and player die-roll is equal
-            // die[1]=-3;die[2]=-2;die[3]=-1;die[4]=0;die[5]=1;die[6]=2;
-            if diff_of_dice == 0 {
-                diff_of_dice = player_die.clone() - (4 as i8);
-            }
-            let mut newlevel = ticket_data.level + &diff_of_dice;
-            if newlevel < 0{
-                newlevel = 0;
+            // the cooldown only applies after the first round of a ticket
+            let current_epoch = Runtime::current_epoch();
+            assert!(ticket_data.rounds_played == 0 
+                || current_epoch >= ticket_data.last_played_epoch + self.cooldown_epochs,
+                "Ticket is cooling down, next round can be played in epoch {}", 
+                ticket_data.last_played_epoch + self.cooldown_epochs);
+
+            let (house_die, player_die, delta) = round;
+            // all level math is done in i32 and clamped before storing back as i8,
+            // a d20 with a staked multiplier or a streak bonus can push an i8 level past its range
+            let mut diff_of_dice = delta as i32 * multiplier as i32;
+            // a hot streak grants one extra level, after which the streak starts over.
+            // The bonus counts towards max_delta and shows up in the logged level change.
+            if diff_of_dice > 0 {
+                ticket_data.win_streak = ticket_data.win_streak.saturating_add(1);
+                if self.streak_bonus_threshold > 0 
+                    && ticket_data.win_streak >= self.streak_bonus_threshold {
+                    diff_of_dice += 1;
+                    ticket_data.win_streak = 0;
+                }
+            } else if diff_of_dice < 0 {
+                ticket_data.win_streak = 0;
             }
-            if newlevel > 25{
-                newlevel = 25
+            if self.max_delta > 0 {
+                diff_of_dice = diff_of_dice.clamp(-(self.max_delta as i32), self.max_delta as i32);
             }
+            let newlevel = (ticket_data.level as i32 + diff_of_dice).clamp(0, self.win_level as i32) as i8;
             let throw_string: String = format!("House {}, Player {}, New Lvl {}({:+})", 
                                 house_die, player_die, newlevel, diff_of_dice);
+            let old_level = ticket_data.level;
+            self.track_max_level(old_level, newlevel);
             ticket_data.level = newlevel;
+            // keep the last RECENT_THROWS throws, oldest first
+            ticket_data.recent_throws.push(throw_string.clone());
+            if ticket_data.recent_throws.len() > RECENT_THROWS {
+                ticket_data.recent_throws.remove(0);
+            }
+            self.recent_global_throws.push(format!("Ticket {}: {}", nft_id, throw_string));
+            if self.recent_global_throws.len() > RECENT_GLOBAL_THROWS {
+                self.recent_global_throws.remove(0);
+            }
             ticket_data.last_throw = throw_string;
+            ticket_data.rounds_played += 1;
+            ticket_data.last_played_epoch = current_epoch;
+            if diff_of_dice > 0 {
+                ticket_data.wins += 1;
+            } else if diff_of_dice < 0 {
+                ticket_data.losses += 1;
+            }
 
-            self.admin_vault.authorize(|| resource_manager.update_non_fungible_data(
+            self.authorize_update(|| resource_manager.update_non_fungible_data(
                 &nft_id, 
                 ticket_data
             ));
+
+            // the level before the round was reached as well
+            let round_high = old_level.max(newlevel);
+            let high_score = self.get_high_score(nft_id.clone());
+            if high_score.map_or(true, |score| round_high > score) {
+                self.high_scores.insert((self.season, nft_id.clone()), round_high);
+            }
+
+            Runtime::emit_event(RoundPlayed {
+                ticket_id: nft_id.clone(),
+                house_die,
+                player_die,
+                old_level,
+                new_level: newlevel,
+            });
+
+            if newlevel == 0 {
+                self.set_aside_consolation(nft_id);
+            }
+
+            newlevel
+        }
+
+        /*
+            Set aside the bust consolation for a ticket which dropped to level 0,
+            only when the main wallet can pay it without breaching the minimum reserve.
+            The ticket stays on level 0 and can still be reinitialized or refunded.
+        */
+        fn set_aside_consolation(&mut self, nft_id: NonFungibleLocalId) {
+            if self.bust_consolation == dec!("0") {
+                return;
+            }
+            let mut consolation = dec!("0");
+            if self.bust_consolation <= self.available_above_reserve() {
+                consolation = self.bust_consolation;
+                self.consolation_vault.put(self.radix_vault.take(consolation));
+                self.vault_changed("consolation");
+                let unclaimed = self.get_consolation(nft_id.clone());
+                self.consolations.insert(nft_id.clone(), unclaimed + consolation);
+            }
+            Runtime::emit_event(Busted {
+                ticket_id: nft_id,
+                consolation,
+            });
+        }
+
+        /*
+            Unclaimed consolation of a ticket, 0 when nothing was set aside
+        */
+        pub fn get_consolation(&self, nft_id: NonFungibleLocalId) -> Decimal {
+            self.consolations.get(&nft_id).map_or(dec!("0"), |amount| *amount)
+        }
+
+        /*
+            Claim the consolation set aside for a ticket which dropped to level 0
+        */
+        pub fn claim_consolation(&mut self, NFTTicket: Proof) -> Bucket {
+            let nft_id = self.validate_ticket_proof(NFTTicket);
+            let unclaimed = self.get_consolation(nft_id.clone());
+            assert!(unclaimed > dec!("0"), "No consolation to claim for this ticket");
+            self.consolations.insert(nft_id, dec!("0"));
+            self.consolation_vault.take(unclaimed)
+        }
+
+        /*
+            Play a round of RadiceX. Two dice are rolled, ties are rolled again
+            the diff between the player value and house value is calculated and added to the NFT level field.
+            If the player reaches the win level, this token can be redeemed for the prize amount
+            If the player reaches level 0, this ticket is no longer playable.
+            The new ticket level is returned.
+        */
+        pub fn play_round(&mut self, NFTTicket: Proof) -> i8 {
+
+            assert!(!self.paused, "Game is paused");
+            let nft_id = self.validate_ticket_proof(NFTTicket);
+
+            self.play_ticket(nft_id, 1)
+        }
+
+        /*
+            Play a cooperative round with a proof of up to MAX_TEAM_SIZE tickets.
+            The dice are rolled once and the same level delta is applied to every ticket of the team,
+            every ticket has to be playable.
+        */
+        pub fn play_team_round(&mut self, tickets: Proof) {
+
+            assert!(!self.paused, "Game is paused");
+            let validated_proof = tickets.validate_proof(
+                ProofValidationMode::ValidateResourceAddress(self.my_non_fungible_ticket)
+            ).expect("Invalid proof, the proof is not a RaDiceX ticket");
+            let nft_ids = validated_proof.non_fungible_local_ids();
+            assert!(!nft_ids.is_empty() && nft_ids.len() <= MAX_TEAM_SIZE, 
+                "A team has between 1 and {} tickets, got {}", MAX_TEAM_SIZE, nft_ids.len());
+
+            let round = self.throw_round();
+            for nft_id in nft_ids {
+                self.apply_round(nft_id, round, 1);
+            }
+        }
+
+        /*
+            Play a round of RadiceX, but only keep it when the new level is at least min_level_after.
+            The dice can't be rolled again, a bad roll panics which reverts the whole transaction,
+            so the ticket keeps its level and only the transaction fee is lost.
+        */
+        pub fn play_round_min(&mut self, NFTTicket: Proof, min_level_after: i8) -> i8 {
+
+            assert!(!self.paused, "Game is paused");
+            let nft_id = self.validate_ticket_proof(NFTTicket);
+
+            let newlevel = self.play_ticket(nft_id, 1);
+            assert!(newlevel >= min_level_after, 
+                "The round ends on level {}, below the minimum level {}, the round is reverted", 
+                newlevel, min_level_after);
+            newlevel
+        }
+
+        /*
+            Play up to `rounds` rounds of RadiceX in one call, stopping early when the ticket
+            reaches level 0 or the win level. The final ticket level is returned.
+            With a cooldown set only the first round is played, the next one would still be cooling down.
+        */
+        pub fn play_rounds(&mut self, NFTTicket: Proof, rounds: u8) -> i8 {
+
+            assert!(!self.paused, "Game is paused");
+            assert!(rounds > 0, "At least one round has to be played");
+            let nft_id = self.validate_ticket_proof(NFTTicket);
+
+            let mut level = self.play_ticket(nft_id.clone(), 1);
+            for _ in 1..rounds {
+                if level == 0 || level == self.win_level || self.cooldown_epochs > 0 {
+                    break;
+                }
+                level = self.play_ticket(nft_id.clone(), 1);
+            }
+            level
         }
+
+        /*
+            Play a round of RadiceX with the ticket itself instead of a proof, the ticket is send back.
+            Easier to call from a simple manifest, the new level is in the RoundPlayed event.
+        */
+        pub fn play_round_with_bucket(&mut self, NFTTicket: Bucket) -> Bucket {
+
+            assert!(!self.paused, "Game is paused");
+            assert!(
+                NFTTicket.resource_address() == self.my_non_fungible_ticket,
+                "The supplied bucket does not contain the correct Ticket address"
+            );
+            assert!(NFTTicket.amount()==dec!("1"), "Only one (1) ticket per call is supported");
+
+            self.play_ticket(NFTTicket.non_fungible_local_id(), 1);
+
+            NFTTicket
+        }
+
+        /*
+            Play a staked round of RadiceX, the diff of the dice is multiplied by the multiplier (1 to 3).
+            Faster progress, but also bigger losses. The stake costs the ticket price for every 
+            multiplier step above 1 and goes to the main wallet, not the prize vault.
+            The new ticket level and the change of the stake are returned.
+        */
+        pub fn play_round_staked(&mut self, NFTTicket: Proof, mut stake: Bucket, multiplier: u8) 
+            -> (i8, Bucket) {
+
+            assert!(!self.paused, "Game is paused");
+            assert!(
+                stake.resource_address() == self.accepted_buyin,
+                "The stake can only be done with the accepted buy-in resource"
+            );
+            assert!(multiplier >= 1 && multiplier <= MAX_STAKE_MULTIPLIER, 
+                "The multiplier must be between 1 and {}", MAX_STAKE_MULTIPLIER);
+
+            let amount: Decimal = self.buyin_price(self.ticket_price * (multiplier - 1));
+            assert!(!(stake.amount()<amount), "Not enough XRD supplied");
+
+            let nft_id = self.validate_ticket_proof(NFTTicket);
+
+            self.radix_vault.put(stake.take(amount));
+            self.vault_changed("stake");
+
+            (self.play_ticket(nft_id, multiplier as i8), stake)
+        }
+
         /*
-            Burning of a NFT ticket
+            Burning of a NFT ticket, holding the ticket bucket is proof of ownership.
+            The level of the ticket at the time of burning is returned and emitted in a TicketBurned event.
         */
-        pub fn burn_ticket(&mut self, NFTTicket: Bucket) {
+        pub fn burn_ticket(&mut self, NFTTicket: Bucket) -> i8 {
+            self.assert_admin_badge_present();
             assert!(
                 NFTTicket.resource_address() == self.my_non_fungible_ticket,
                 "The supplied bucket does not contain the correct Ticket address"
@@ -323,8 +2597,87 @@ mod mod_radicex{
 
             let resource_manager: &mut ResourceManager = 
                 borrow_resource_manager!(self.my_non_fungible_ticket);
+
+            let ticket_id = NFTTicket.non_fungible_local_id();
+            let ticket_data: Ticket = resource_manager.get_non_fungible_data(&ticket_id);
+            self.track_max_level(ticket_data.level, 0);
+            Runtime::emit_event(TicketBurned { ticket_id, level: ticket_data.level });
     
-            self.admin_vault.authorize(|| resource_manager.burn(NFTTicket));
+            self.authorize_update(|| resource_manager.burn(NFTTicket));
+
+            ticket_data.level
+        }
+
+        /*
+            Burning of any number of NFT tickets in one call
+        */
+        pub fn burn_tickets(&mut self, NFTTickets: Bucket) {
+            self.assert_admin_badge_present();
+            assert!(
+                NFTTickets.resource_address() == self.my_non_fungible_ticket,
+                "The supplied bucket does not contain the correct Ticket address"
+            );
+            assert!(!NFTTickets.is_empty(), "The supplied bucket is empty");
+
+            let resource_manager: &mut ResourceManager = 
+                borrow_resource_manager!(self.my_non_fungible_ticket);
+
+            for ticket_id in NFTTickets.non_fungible_local_ids() {
+                let ticket_data: Ticket = resource_manager.get_non_fungible_data(&ticket_id);
+                self.track_max_level(ticket_data.level, 0);
+                Runtime::emit_event(TicketBurned { ticket_id, level: ticket_data.level });
+            }
+
+            self.authorize_update(|| resource_manager.burn(NFTTickets));
+        }
+
+        /*
+            Fuse exactly two tickets into one new ticket on the average level of both, rounded down
+            Both tickets are burned, the new ticket counts against the cap on the number of tickets.
+        */
+        pub fn fuse_tickets(&mut self, NFTTickets: Bucket) -> Bucket {
+            assert!(!self.paused, "Game is paused");
+            assert!(
+                NFTTickets.resource_address() == self.my_non_fungible_ticket,
+                "The supplied bucket does not contain the correct Ticket address"
+            );
+            assert!(NFTTickets.amount()==dec!("2"), "Exactly two (2) tickets can be fused");
+
+            let resource_manager: &mut ResourceManager = 
+                borrow_resource_manager!(self.my_non_fungible_ticket);
+            let mut total_level: i32 = 0;
+            for ticket_id in NFTTickets.non_fungible_local_ids() {
+                let ticket_data: Ticket = resource_manager.get_non_fungible_data(&ticket_id);
+                total_level += ticket_data.level as i32;
+            }
+
+            self.burn_tickets(NFTTickets);
+            self.mint_ticket((total_level / 2) as i8, "Fused")
+        }
+
+        /*
+            Burn a level 0 ticket and get the dead ticket refund from the main wallet in return,
+            the minimum reserve is never paid out.
+        */
+        pub fn refund_dead_ticket(&mut self, NFTTicket: Bucket) -> Bucket {
+            assert!(!self.paused, "Game is paused");
+            assert!(
+                NFTTicket.resource_address() == self.my_non_fungible_ticket,
+                "The supplied bucket does not contain the correct Ticket address"
+            );
+            assert!(NFTTicket.amount()==dec!("1"), "Only one (1) ticket per call is supported");
+
+            let ticket_data: Ticket = borrow_resource_manager!(self.my_non_fungible_ticket)
+                .get_non_fungible_data(&NFTTicket.non_fungible_local_id());
+            assert!(ticket_data.level == 0, "Level not 0, Ticket still playable");
+            assert!(self.dead_ticket_refund <= self.available_above_reserve(), 
+                "Not enough funds above the minimum reserve to pay the refund");
+
+            self.burn_ticket(NFTTicket);
+
+            let refund = self.radix_vault.take(self.dead_ticket_refund);
+            self.vault_changed("dead_ticket_refund");
+            refund
         }
 
     }
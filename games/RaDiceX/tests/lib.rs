@@ -0,0 +1,3804 @@
+use radix_engine::transaction::TransactionReceipt;
+use radicex::{
+    delta_distribution, expected_delta, roll_die, roll_die_modulo, single_die_step, throw_dice,
+    throw_from_raws, win_probability, Busted, DiceDrawn, FundsWithdrawn, GameSnapshot,
+    InstantPlayed, LevelAdjusted, PrizeAmountChanged, PrizeRedeemed, PrizeUnderfunded,
+    RngOverrideSet, RoundPlayed, SeasonReset, SweepPerformed, TicketBurned, TicketMinted,
+    TicketPurchased, TicketReinitialized, VaultChanged, DICE_MODE_FAIR, DICE_MODE_HOUSE_FAVORED,
+    DICE_MODE_PLAYER_FAVORED, MAX_DICE_FACES, MAX_NICKNAME_LENGTH, MAX_REROLLS, MAX_ROLL_ATTEMPTS,
+    MAX_TEAM_SIZE, MIN_DICE_FACES, RECENT_GLOBAL_THROWS, RECENT_THROWS, RNG_STRATEGY_MODULO,
+    RNG_STRATEGY_REJECTION,
+};
+use radix_engine_interface::model::FromPublicKey;
+use scrypto::prelude::*;
+use scrypto_unit::*;
+use transaction::builder::ManifestBuilder;
+use transaction::model::TransactionManifest;
+
+// Instantiation parameters of a RaDiceX component
+struct Config {
+    win_level: i8,
+    start_level: i8,
+    // pay prizes in the test reward token instead of XRD
+    reward_token_prize: bool,
+    prize_amount: Decimal,
+    prize_tiers: Vec<(i8, Decimal)>,
+    ticket_price: Decimal,
+    reinit_discount: Decimal,
+    dice_mode: u8,
+    dice_faces: u8,
+    single_die_mode: bool,
+    max_tickets: u64,
+    ticket_description: Option<String>,
+    ticket_icon_url: Option<String>,
+    ticket_tags: Option<Vec<String>>,
+    // accept the test reward token for buy-ins instead of XRD
+    reward_token_buyin: bool,
+    // number of decimals of the test reward token
+    reward_token_divisibility: u8,
+    // let anyone call roll_dice_preview
+    public_preview: bool,
+    // let the admin force the dice with set_rng_override
+    allow_rng_override: bool,
+    // instantiate a fixed-odds game with this payout table, only the dice settings are used
+    payout_table: Option<Vec<(i8, Decimal)>>,
+}
+
+impl Default for Config {
+    // the classic 25/5 setup with a 1 XRD ticket
+    fn default() -> Self {
+        Self {
+            win_level: 25,
+            start_level: 10,
+            reward_token_prize: false,
+            prize_amount: dec!("5"),
+            prize_tiers: Vec::new(),
+            ticket_price: dec!("1"),
+            reinit_discount: dec!("0.9"),
+            dice_mode: DICE_MODE_FAIR,
+            dice_faces: 6,
+            single_die_mode: false,
+            max_tickets: 0,
+            ticket_description: None,
+            ticket_icon_url: None,
+            ticket_tags: None,
+            reward_token_buyin: false,
+            reward_token_divisibility: 18,
+            public_preview: true,
+            allow_rng_override: true,
+            payout_table: None,
+        }
+    }
+}
+
+// Small deterministic xorshift generator standing in for Runtime::generate_uuid
+struct XorShift(u64);
+
+impl XorShift {
+    fn step(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn next(&mut self) -> u128 {
+        ((self.step() as u128) << 64) | self.step() as u128
+    }
+}
+
+// Publish the package and try to instantiate a RaDiceX component with the given config
+// A reward token with a supply of 1000 is created in the account before instantiating
+fn instantiate(
+    config: Config,
+) -> (
+    TestRunner,
+    EcdsaSecp256k1PublicKey,
+    ComponentAddress,
+    ResourceAddress,
+    TransactionReceipt,
+) {
+    let mut test_runner = TestRunner::builder().build();
+    let (public_key, _private_key, account) = test_runner.new_allocated_account();
+    let package_address = test_runner.compile_and_publish(this_package!());
+    let divisibility = config.reward_token_divisibility;
+    let reward_token = test_runner.create_fungible_resource(dec!("1000"), divisibility, account);
+    let prize_resource = if config.reward_token_prize {
+        reward_token
+    } else {
+        RADIX_TOKEN
+    };
+    let accepted_buyin = if config.reward_token_buyin {
+        Some(reward_token)
+    } else {
+        None
+    };
+
+    let (function, args) = match config.payout_table {
+        Some(payout_table) => (
+            "instantiate_fixed_odds",
+            args!(
+                config.dice_mode,
+                config.dice_faces,
+                payout_table,
+                accepted_buyin,
+                config.allow_rng_override
+            ),
+        ),
+        None => (
+            "instantiate_with_tiers",
+            args!(
+                config.win_level,
+                config.start_level,
+                prize_resource,
+                config.prize_amount,
+                config.prize_tiers,
+                config.ticket_price,
+                config.reinit_discount,
+                config.dice_mode,
+                config.dice_faces,
+                config.single_die_mode,
+                config.max_tickets,
+                config.ticket_description,
+                config.ticket_icon_url,
+                config.ticket_tags,
+                accepted_buyin,
+                config.public_preview,
+                config.allow_rng_override
+            ),
+        ),
+    };
+    let manifest = ManifestBuilder::new()
+        .call_function(package_address, "Radicex", function, args)
+        .call_method(
+            account,
+            "deposit_batch",
+            args!(ManifestExpression::EntireWorktop),
+        )
+        .build();
+    let receipt = test_runner.execute_manifest_ignoring_fee(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&public_key)],
+    );
+    println!("{:?}\n", receipt);
+
+    (test_runner, public_key, account, reward_token, receipt)
+}
+
+struct TestEnv {
+    test_runner: TestRunner,
+    public_key: EcdsaSecp256k1PublicKey,
+    account: ComponentAddress,
+    component: ComponentAddress,
+    admin_badge: ResourceAddress,
+    minter_badge: ResourceAddress,
+    updater_badge: ResourceAddress,
+    ticket: ResourceAddress,
+    reward_token: ResourceAddress,
+    // number of tickets minted by the component so far
+    minted: u64,
+}
+
+impl TestEnv {
+    fn new() -> Self {
+        Self::with_config(Config::default())
+    }
+
+    fn with_config(config: Config) -> Self {
+        let (test_runner, public_key, account, reward_token, receipt) = instantiate(config);
+        receipt.expect_commit_success();
+        let commit = receipt.expect_commit();
+        let component = commit.entity_changes.new_component_addresses[0];
+        // resource 0 is the admin badge, 1 and 2 the minter and updater badges, 3 the ticket NFT
+        let admin_badge = commit.entity_changes.new_resource_addresses[0];
+        let minter_badge = commit.entity_changes.new_resource_addresses[1];
+        let updater_badge = commit.entity_changes.new_resource_addresses[2];
+        let ticket = commit.entity_changes.new_resource_addresses[3];
+
+        Self {
+            test_runner,
+            public_key,
+            account,
+            component,
+            admin_badge,
+            minter_badge,
+            updater_badge,
+            ticket,
+            reward_token,
+            minted: 0,
+        }
+    }
+
+    fn execute(&mut self, manifest: TransactionManifest) -> TransactionReceipt {
+        let receipt = self.test_runner.execute_manifest_ignoring_fee(
+            manifest,
+            vec![NonFungibleGlobalId::from_public_key(&self.public_key)],
+        );
+        println!("{:?}\n", receipt);
+        receipt
+    }
+
+    // Decode all application events with the given name from a receipt
+    fn events<T: ScryptoDecode>(&self, receipt: &TransactionReceipt, name: &str) -> Vec<T> {
+        receipt
+            .expect_commit()
+            .application_events
+            .iter()
+            .filter(|(event_type, _)| self.test_runner.event_name(event_type) == name)
+            .map(|(_, data)| scrypto_decode::<T>(data).unwrap())
+            .collect()
+    }
+
+    // Call a read-only method on the component and decode its return value
+    fn read<T: ScryptoDecode>(&mut self, method: &str, args: Vec<u8>) -> T {
+        let manifest = ManifestBuilder::new()
+            .call_method(self.component, method, args)
+            .build();
+        let receipt = self.execute(manifest);
+        receipt.expect_commit_success();
+        receipt.expect_commit().output(0)
+    }
+
+    // Call a method with a proof of both admin badges of the account in the auth zone
+    fn admin_call(&mut self, method: &str, args: Vec<u8>) -> TransactionReceipt {
+        self.admin_call_with_badges(dec!("2"), method, args)
+    }
+
+    // Call a method with a proof of `badges` admin badges in the auth zone
+    fn admin_call_with_badges(
+        &mut self,
+        badges: Decimal,
+        method: &str,
+        args: Vec<u8>,
+    ) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_by_amount(self.account, badges, self.admin_badge)
+            .call_method(self.component, method, args)
+            .call_method(
+                self.account,
+                "deposit_batch",
+                args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+        self.execute(manifest)
+    }
+
+    fn xrd_balance(&mut self) -> Decimal {
+        self.test_runner
+            .account_balance(self.account, RADIX_TOKEN)
+            .unwrap_or_default()
+    }
+
+    // Call `deposit` with `supplied` XRD taken from the account, asking to deposit `amount`
+    fn deposit(&mut self, amount: Decimal, supplied: Decimal) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .withdraw_from_account_by_amount(self.account, supplied, RADIX_TOKEN)
+            .take_from_worktop(RADIX_TOKEN, |builder, bucket_id| {
+                builder.call_method(self.component, "deposit", args!(amount, bucket_id))
+            })
+            .call_method(
+                self.account,
+                "deposit_batch",
+                args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+        self.execute(manifest)
+    }
+
+    // Put `amount` XRD taken from the account in the main wallet
+    fn fund_house(&mut self, resource: ResourceAddress, amount: Decimal) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .withdraw_from_account_by_amount(self.account, amount, resource)
+            .take_from_worktop(resource, |builder, bucket_id| {
+                builder.call_method(self.component, "fund_house", args!(bucket_id))
+            })
+            .build();
+        self.execute(manifest)
+    }
+
+    // Stake `stake` XRD taken from the account on an instant round of a fixed-odds game
+    fn play_instant(&mut self, stake: Decimal) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .withdraw_from_account_by_amount(self.account, stake, RADIX_TOKEN)
+            .take_from_worktop(RADIX_TOKEN, |builder, bucket_id| {
+                builder.call_method(self.component, "play_instant", args!(bucket_id))
+            })
+            .call_method(
+                self.account,
+                "deposit_batch",
+                args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+        self.execute(manifest)
+    }
+
+    // Admin funds the prize vault with `amount` of `resource` taken from the account
+    fn fund_prize_vault(
+        &mut self,
+        resource: ResourceAddress,
+        amount: Decimal,
+    ) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account(self.account, self.admin_badge)
+            .withdraw_from_account_by_amount(self.account, amount, resource)
+            .take_from_worktop(resource, |builder, bucket_id| {
+                builder.call_method(self.component, "fund_prize_vault", args!(bucket_id))
+            })
+            .build();
+        self.execute(manifest)
+    }
+
+    // Hand a withdrawn internal `badge` to a new component for the same tickets
+    fn instantiate_with_resource(
+        &mut self,
+        badge: ResourceAddress,
+        nr_tickets_minted: u64,
+    ) -> TransactionReceipt {
+        let package_address = self.test_runner.compile_and_publish(this_package!());
+        let ticket = self.ticket;
+        let manifest = ManifestBuilder::new()
+            .withdraw_from_account_by_amount(self.account, dec!("1"), badge)
+            .take_from_worktop(badge, |builder, bucket_id| {
+                builder.call_function(
+                    package_address,
+                    "Radicex",
+                    "instantiate_with_resource",
+                    args!(
+                        ticket,
+                        bucket_id,
+                        nr_tickets_minted,
+                        25i8,
+                        10i8,
+                        RADIX_TOKEN,
+                        dec!("5"),
+                        dec!("1"),
+                        dec!("0.9"),
+                        DICE_MODE_FAIR,
+                        6u8,
+                        false,
+                        0u64
+                    ),
+                )
+            })
+            .build();
+        self.execute(manifest)
+    }
+
+    // Ticket ids are integers, handed out in order of minting starting at 1
+    fn ticket_id(&self, n: u64) -> NonFungibleLocalId {
+        NonFungibleLocalId::Integer(n.into())
+    }
+
+    fn last_ticket_id(&self) -> NonFungibleLocalId {
+        self.ticket_id(self.minted)
+    }
+
+    // Force the dice until the ticket reaches the win level, the dice are rolled again afterwards
+    fn play_to_win(&mut self, ticket_id: &NonFungibleLocalId) {
+        let busted = self.play_until_finished(ticket_id, (1, 6));
+        assert!(!busted, "The ticket went bust with forced winning dice");
+    }
+
+    fn set_ticket_level(
+        &mut self,
+        ticket_id: &NonFungibleLocalId,
+        level: i8,
+    ) -> TransactionReceipt {
+        self.admin_call("set_ticket_level", args!(ticket_id.clone(), level))
+    }
+
+    fn buy_ticket(&mut self, supplied: Decimal) -> TransactionReceipt {
+        self.buy_ticket_with(RADIX_TOKEN, supplied)
+    }
+
+    // Buy a ticket paying with `supplied` of `resource` taken from the account
+    fn buy_ticket_with(
+        &mut self,
+        resource: ResourceAddress,
+        supplied: Decimal,
+    ) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .withdraw_from_account_by_amount(self.account, supplied, resource)
+            .take_from_worktop(resource, |builder, bucket_id| {
+                builder.call_method(self.component, "buy_ticket", args!(bucket_id))
+            })
+            .call_method(
+                self.account,
+                "deposit_batch",
+                args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+        let receipt = self.execute(manifest);
+        if receipt.is_commit_success() {
+            self.minted += 1;
+        }
+        receipt
+    }
+
+    fn buy_ticket_referred(
+        &mut self,
+        referrer: &NonFungibleLocalId,
+        supplied: Decimal,
+    ) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .withdraw_from_account_by_amount(self.account, supplied, RADIX_TOKEN)
+            .take_from_worktop(RADIX_TOKEN, |builder, bucket_id| {
+                builder.call_method(
+                    self.component,
+                    "buy_ticket_referred",
+                    args!(bucket_id, referrer.clone()),
+                )
+            })
+            .call_method(
+                self.account,
+                "deposit_batch",
+                args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+        let receipt = self.execute(manifest);
+        if receipt.is_commit_success() {
+            self.minted += 1;
+        }
+        receipt
+    }
+
+    fn claim_referral(&mut self, ticket_id: &NonFungibleLocalId) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_by_ids(
+                self.account,
+                &BTreeSet::from([ticket_id.clone()]),
+                self.ticket,
+            )
+            .pop_from_auth_zone(|builder, proof_id| {
+                builder.call_method(self.component, "claim_referral", args!(proof_id))
+            })
+            .call_method(
+                self.account,
+                "deposit_batch",
+                args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+        self.execute(manifest)
+    }
+
+    fn buy_tickets(&mut self, count: u32, supplied: Decimal) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .withdraw_from_account_by_amount(self.account, supplied, RADIX_TOKEN)
+            .take_from_worktop(RADIX_TOKEN, |builder, bucket_id| {
+                builder.call_method(self.component, "buy_tickets", args!(count, bucket_id))
+            })
+            .call_method(
+                self.account,
+                "deposit_batch",
+                args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+        let receipt = self.execute(manifest);
+        if receipt.is_commit_success() {
+            self.minted += count as u64;
+        }
+        receipt
+    }
+
+    fn play_round(&mut self, ticket_id: &NonFungibleLocalId) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_by_ids(
+                self.account,
+                &BTreeSet::from([ticket_id.clone()]),
+                self.ticket,
+            )
+            .pop_from_auth_zone(|builder, proof_id| {
+                builder.call_method(self.component, "play_round", args!(proof_id))
+            })
+            .build();
+        self.execute(manifest)
+    }
+
+    // Play a team round with a proof of all given tickets
+    fn play_team_round(&mut self, ticket_ids: BTreeSet<NonFungibleLocalId>) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_by_ids(self.account, &ticket_ids, self.ticket)
+            .pop_from_auth_zone(|builder, proof_id| {
+                builder.call_method(self.component, "play_team_round", args!(proof_id))
+            })
+            .build();
+        self.execute(manifest)
+    }
+
+    fn play_rounds(&mut self, ticket_id: &NonFungibleLocalId, rounds: u8) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_by_ids(
+                self.account,
+                &BTreeSet::from([ticket_id.clone()]),
+                self.ticket,
+            )
+            .pop_from_auth_zone(|builder, proof_id| {
+                builder.call_method(self.component, "play_rounds", args!(proof_id, rounds))
+            })
+            .build();
+        self.execute(manifest)
+    }
+
+    fn play_round_min(
+        &mut self,
+        ticket_id: &NonFungibleLocalId,
+        min_level_after: i8,
+    ) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_by_ids(
+                self.account,
+                &BTreeSet::from([ticket_id.clone()]),
+                self.ticket,
+            )
+            .pop_from_auth_zone(|builder, proof_id| {
+                builder.call_method(
+                    self.component,
+                    "play_round_min",
+                    args!(proof_id, min_level_after),
+                )
+            })
+            .build();
+        self.execute(manifest)
+    }
+
+    fn set_nickname(&mut self, ticket_id: &NonFungibleLocalId, name: &str) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_by_ids(
+                self.account,
+                &BTreeSet::from([ticket_id.clone()]),
+                self.ticket,
+            )
+            .pop_from_auth_zone(|builder, proof_id| {
+                builder.call_method(self.component, "set_nickname", args!(proof_id, name))
+            })
+            .build();
+        self.execute(manifest)
+    }
+
+    fn play_round_with_bucket(&mut self, ticket_id: &NonFungibleLocalId) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .withdraw_from_account_by_ids(
+                self.account,
+                &BTreeSet::from([ticket_id.clone()]),
+                self.ticket,
+            )
+            .take_from_worktop(self.ticket, |builder, bucket_id| {
+                builder.call_method(self.component, "play_round_with_bucket", args!(bucket_id))
+            })
+            .call_method(
+                self.account,
+                "deposit_batch",
+                args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+        self.execute(manifest)
+    }
+
+    fn play_round_staked(
+        &mut self,
+        ticket_id: &NonFungibleLocalId,
+        stake: Decimal,
+        multiplier: u8,
+    ) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .withdraw_from_account_by_amount(self.account, stake, RADIX_TOKEN)
+            .create_proof_from_account_by_ids(
+                self.account,
+                &BTreeSet::from([ticket_id.clone()]),
+                self.ticket,
+            )
+            .pop_from_auth_zone(|builder, proof_id| {
+                builder.take_from_worktop(RADIX_TOKEN, |builder, bucket_id| {
+                    builder.call_method(
+                        self.component,
+                        "play_round_staked",
+                        args!(proof_id, bucket_id, multiplier),
+                    )
+                })
+            })
+            .call_method(
+                self.account,
+                "deposit_batch",
+                args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+        self.execute(manifest)
+    }
+
+    // Force the (house, player) dice until the ticket is no longer playable, the dice are rolled
+    // again afterwards. Returns true if the ticket went bust, false if it reached the win level
+    fn play_until_finished(&mut self, ticket_id: &NonFungibleLocalId, dice: (i8, i8)) -> bool {
+        let (_, _, _, win_level): (Decimal, Decimal, i8, i8) = self.read("get_config", args!());
+        self.admin_call("set_rng_override", args!(Some(dice)))
+            .expect_commit_success();
+        for _ in 0..500 {
+            let receipt = self.play_round(ticket_id);
+            let level: i8 = receipt.expect_commit().output(2);
+            if level == 0 || level == win_level {
+                self.admin_call("set_rng_override", args!(None::<(i8, i8)>))
+                    .expect_commit_success();
+                return level == 0;
+            }
+        }
+        panic!("Ticket still playable after 500 rounds");
+    }
+
+    // Buy a ticket and force it to the win level, returns the id of the winning ticket
+    fn win_ticket(&mut self, price: Decimal) -> NonFungibleLocalId {
+        self.buy_ticket(price).expect_commit_success();
+        let ticket_id = self.last_ticket_id();
+        self.play_to_win(&ticket_id);
+        ticket_id
+    }
+
+    // Buy a ticket and force it to level 0, returns the id of the level 0 ticket
+    fn bust_ticket(&mut self, price: Decimal) -> NonFungibleLocalId {
+        self.buy_ticket(price).expect_commit_success();
+        let ticket_id = self.last_ticket_id();
+        let busted = self.play_until_finished(&ticket_id, (6, 1));
+        assert!(busted, "The ticket reached the win level with forced losing dice");
+        ticket_id
+    }
+
+    // Force a ticket from level 1 to level 0, returns the receipt of the losing round
+    fn bust_from_level_one(&mut self, ticket_id: &NonFungibleLocalId) -> TransactionReceipt {
+        self.set_ticket_level(ticket_id, 1).expect_commit_success();
+        self.admin_call("set_rng_override", args!(Some((6i8, 1i8))))
+            .expect_commit_success();
+        let receipt = self.play_round(ticket_id);
+        let level: i8 = receipt.expect_commit().output(2);
+        assert_eq!(level, 0);
+        self.admin_call("set_rng_override", args!(None::<(i8, i8)>))
+            .expect_commit_success();
+        receipt
+    }
+
+    fn claim_consolation(&mut self, ticket_id: &NonFungibleLocalId) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_by_ids(
+                self.account,
+                &BTreeSet::from([ticket_id.clone()]),
+                self.ticket,
+            )
+            .pop_from_auth_zone(|builder, proof_id| {
+                builder.call_method(self.component, "claim_consolation", args!(proof_id))
+            })
+            .call_method(
+                self.account,
+                "deposit_batch",
+                args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+        self.execute(manifest)
+    }
+
+    fn redeem_prize(&mut self, ticket_id: &NonFungibleLocalId) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_by_ids(
+                self.account,
+                &BTreeSet::from([ticket_id.clone()]),
+                self.ticket,
+            )
+            .pop_from_auth_zone(|builder, proof_id| {
+                builder.call_method(self.component, "redeem_prize", args!(proof_id))
+            })
+            .call_method(
+                self.account,
+                "deposit_batch",
+                args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+        self.execute(manifest)
+    }
+
+    fn burn_ticket(&mut self, ticket_id: &NonFungibleLocalId) -> TransactionReceipt {
+        self.burn_ticket_of(self.ticket, ticket_id)
+    }
+
+    // Burn with a bucket of the given non fungible resource, which may not be a ticket
+    fn burn_ticket_of(
+        &mut self,
+        resource: ResourceAddress,
+        ticket_id: &NonFungibleLocalId,
+    ) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .withdraw_from_account_by_ids(
+                self.account,
+                &BTreeSet::from([ticket_id.clone()]),
+                resource,
+            )
+            .take_from_worktop(resource, |builder, bucket_id| {
+                builder.call_method(self.component, "burn_ticket", args!(bucket_id))
+            })
+            .build();
+        self.execute(manifest)
+    }
+
+    fn fuse_tickets(&mut self, ticket_ids: BTreeSet<NonFungibleLocalId>) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .withdraw_from_account_by_ids(self.account, &ticket_ids, self.ticket)
+            .take_from_worktop(self.ticket, |builder, bucket_id| {
+                builder.call_method(self.component, "fuse_tickets", args!(bucket_id))
+            })
+            .call_method(
+                self.account,
+                "deposit_batch",
+                args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+        self.execute(manifest)
+    }
+
+    fn burn_tickets(&mut self, ticket_ids: BTreeSet<NonFungibleLocalId>) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .withdraw_from_account_by_ids(self.account, &ticket_ids, self.ticket)
+            .take_from_worktop(self.ticket, |builder, bucket_id| {
+                builder.call_method(self.component, "burn_tickets", args!(bucket_id))
+            })
+            .build();
+        self.execute(manifest)
+    }
+
+    fn refund_dead_ticket(&mut self, ticket_id: &NonFungibleLocalId) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .withdraw_from_account_by_ids(
+                self.account,
+                &BTreeSet::from([ticket_id.clone()]),
+                self.ticket,
+            )
+            .take_from_worktop(self.ticket, |builder, bucket_id| {
+                builder.call_method(self.component, "refund_dead_ticket", args!(bucket_id))
+            })
+            .call_method(
+                self.account,
+                "deposit_batch",
+                args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+        self.execute(manifest)
+    }
+
+    fn reinit_ticket(
+        &mut self,
+        ticket_id: &NonFungibleLocalId,
+        supplied: Decimal,
+    ) -> TransactionReceipt {
+        self.reinit_ticket_of(self.ticket, ticket_id, supplied)
+    }
+
+    // Reinitialize with a proof of the given non fungible resource, which may not be a ticket
+    fn reinit_ticket_of(
+        &mut self,
+        resource: ResourceAddress,
+        ticket_id: &NonFungibleLocalId,
+        supplied: Decimal,
+    ) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .withdraw_from_account_by_amount(self.account, supplied, RADIX_TOKEN)
+            .create_proof_from_account_by_ids(
+                self.account,
+                &BTreeSet::from([ticket_id.clone()]),
+                resource,
+            )
+            .pop_from_auth_zone(|builder, proof_id| {
+                builder.take_from_worktop(RADIX_TOKEN, |builder, bucket_id| {
+                    builder.call_method(
+                        self.component,
+                        "reinit_ticket",
+                        args!(proof_id, bucket_id),
+                    )
+                })
+            })
+            .call_method(
+                self.account,
+                "deposit_batch",
+                args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+        self.execute(manifest)
+    }
+}
+
+#[test]
+fn test_deposit_exact_amount() {
+    let mut env = TestEnv::new();
+    let balance_before = env.xrd_balance();
+
+    let receipt = env.deposit(dec!("100"), dec!("100"));
+    receipt.expect_commit_success();
+
+    // the surplus bucket returned to the account is empty, so exactly 100 XRD left the account
+    assert_eq!(env.xrd_balance(), balance_before - dec!("100"));
+}
+
+#[test]
+fn test_deposit_returns_surplus() {
+    let mut env = TestEnv::new();
+    let balance_before = env.xrd_balance();
+
+    let receipt = env.deposit(dec!("100"), dec!("101"));
+    receipt.expect_commit_success();
+
+    assert_eq!(env.xrd_balance(), balance_before - dec!("100"));
+}
+
+#[test]
+fn test_vault_balance() {
+    let mut env = TestEnv::new();
+    assert_eq!(env.read::<Decimal>("vault_balance", args!()), dec!("0"));
+
+    env.deposit(dec!("42"), dec!("50")).expect_commit_success();
+    assert_eq!(env.read::<Decimal>("vault_balance", args!()), dec!("42"));
+}
+
+#[test]
+fn test_deposit_not_enough_tokens() {
+    let mut env = TestEnv::new();
+
+    let receipt = env.deposit(dec!("100"), dec!("99"));
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("supplied 99 but requested 100")
+    });
+}
+
+#[test]
+fn test_partial_withdraw() {
+    let mut env = TestEnv::new();
+    env.deposit(dec!("100"), dec!("100")).expect_commit_success();
+    let balance_before = env.xrd_balance();
+
+    env.admin_call("withdraw", args!(dec!("30"))).expect_commit_success();
+
+    assert_eq!(env.xrd_balance(), balance_before + dec!("30"));
+    assert_eq!(env.read::<Decimal>("vault_balance", args!()), dec!("70"));
+}
+
+#[test]
+fn test_withdraw_more_than_vault() {
+    let mut env = TestEnv::new();
+    env.deposit(dec!("100"), dec!("100")).expect_commit_success();
+
+    let receipt = env.admin_call("withdraw", args!(dec!("101")));
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("requested 101 but only 100 available")
+    });
+}
+
+#[test]
+fn test_custom_ticket_price() {
+    let mut env = TestEnv::with_config(Config {
+        ticket_price: dec!("3"),
+        ..Default::default()
+    });
+
+    let balance_before = env.xrd_balance();
+    env.buy_ticket(dec!("5")).expect_commit_success();
+    assert_eq!(env.xrd_balance(), balance_before - dec!("3"));
+
+    let ticket_id = env.bust_ticket(dec!("3"));
+    let balance_before = env.xrd_balance();
+    env.reinit_ticket(&ticket_id, dec!("5")).expect_commit_success();
+    assert_eq!(env.xrd_balance(), balance_before - dec!("2.7"));
+}
+
+#[test]
+fn test_buy_ticket_not_enough_xrd() {
+    let mut env = TestEnv::with_config(Config {
+        ticket_price: dec!("3"),
+        ..Default::default()
+    });
+
+    let receipt = env.buy_ticket(dec!("2"));
+    receipt.expect_specific_failure(|e| format!("{:?}", e).contains("Not enough XRD supplied"));
+}
+
+#[test]
+fn test_play_round_emits_event() {
+    let mut env = TestEnv::new();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+
+    let receipt = env.play_round(&ticket_id);
+    receipt.expect_commit_success();
+
+    let events: Vec<RoundPlayed> = env.events(&receipt, "RoundPlayed");
+    assert_eq!(events.len(), 1);
+    let event = &events[0];
+    assert_eq!(event.ticket_id, ticket_id);
+    assert_eq!(event.old_level, 10);
+    assert!((1..=6).contains(&event.house_die));
+    assert!((1..=6).contains(&event.player_die));
+    assert!((0..=25).contains(&event.new_level));
+
+    // play_round returns the stored level, instruction 2 is the play_round call
+    let new_level: i8 = receipt.expect_commit().output(2);
+    assert_eq!(new_level, event.new_level);
+}
+
+#[test]
+fn test_tie_is_rerolled() {
+    // house and player both throw 3, followed by a house 2 and player 5
+    let mut rolls = vec![3, 3, 2, 5].into_iter();
+    let (house_die, player_die) = throw_dice(|_| rolls.next().unwrap(), DICE_MODE_FAIR);
+
+    assert_eq!((house_die, player_die), (2, 5));
+    assert!(rolls.next().is_none());
+}
+
+#[test]
+fn test_tie_reroll_is_bounded() {
+    let mut nr_rolls = 0;
+    let (house_die, player_die) = throw_dice(
+        |_| {
+            nr_rolls += 1;
+            4
+        },
+        DICE_MODE_FAIR,
+    );
+
+    // the last tie is kept, which results in a level delta of 0
+    assert_eq!((house_die, player_die), (4, 4));
+    assert_eq!(nr_rolls, 2 * (MAX_REROLLS as u32 + 1));
+}
+
+#[test]
+fn test_roll_die_is_uniform() {
+    let mut rng = XorShift(0x9e37_79b9_7f4a_7c15);
+    let nr_rolls = 60_000;
+    let mut faces = [0u32; 6];
+    for _ in 0..nr_rolls {
+        let die = roll_die(|| rng.next(), 6, false);
+        assert!((1..=6).contains(&die));
+        faces[(die - 1) as usize] += 1;
+    }
+
+    // every face is expected 10000 times, allow 5% deviation
+    for count in faces {
+        assert!((9_500..=10_500).contains(&count), "face count {} out of band", count);
+    }
+}
+
+#[test]
+fn test_paused_game_blocks_play() {
+    let mut env = TestEnv::new();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+
+    env.admin_call("set_paused", args!(true)).expect_commit_success();
+
+    let receipt = env.play_round(&ticket_id);
+    receipt.expect_specific_failure(|e| format!("{:?}", e).contains("Game is paused"));
+    let receipt = env.buy_ticket(dec!("1"));
+    receipt.expect_specific_failure(|e| format!("{:?}", e).contains("Game is paused"));
+
+    env.admin_call("set_paused", args!(false)).expect_commit_success();
+    env.play_round(&ticket_id).expect_commit_success();
+}
+
+#[test]
+fn test_redeem_while_paused() {
+    // a new ticket starts at level 10, so a win level of 11 is reached quickly
+    let mut env = TestEnv::with_config(Config {
+        win_level: 11,
+        ..Default::default()
+    });
+    env.fund_prize_vault(RADIX_TOKEN, dec!("100")).expect_commit_success();
+    let ticket_id = env.win_ticket(dec!("1"));
+
+    env.admin_call("set_paused", args!(true)).expect_commit_success();
+
+    let jackpot: Decimal = env.read("jackpot_balance", args!());
+    let balance_before = env.xrd_balance();
+    env.redeem_prize(&ticket_id).expect_commit_success();
+    assert_eq!(env.xrd_balance(), balance_before + dec!("5") + jackpot);
+}
+
+#[test]
+fn test_redeem_prize_tiers() {
+    // a new ticket starts at level 10, so it can be redeemed for the first tier right away
+    let mut env = TestEnv::with_config(Config {
+        win_level: 11,
+        prize_tiers: vec![(10, dec!("2"))],
+        ..Default::default()
+    });
+    env.fund_prize_vault(RADIX_TOKEN, dec!("100")).expect_commit_success();
+
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let tier_ticket = env.last_ticket_id();
+    let balance_before = env.xrd_balance();
+    env.redeem_prize(&tier_ticket).expect_commit_success();
+    assert_eq!(env.xrd_balance(), balance_before + dec!("2"));
+
+    let win_ticket = env.win_ticket(dec!("1"));
+    let jackpot: Decimal = env.read("jackpot_balance", args!());
+    let balance_before = env.xrd_balance();
+    env.redeem_prize(&win_ticket).expect_commit_success();
+    assert_eq!(env.xrd_balance(), balance_before + dec!("5") + jackpot);
+
+    // both tickets are reset to level 0
+    for ticket_id in [tier_ticket, win_ticket] {
+        let receipt = env.play_round(&ticket_id);
+        receipt.expect_specific_failure(|e| format!("{:?}", e).contains("Ticket Level = 0"));
+    }
+}
+
+#[test]
+fn test_redeem_fee() {
+    // a new ticket starts at level 10, so a win level of 11 is reached quickly
+    let mut env = TestEnv::with_config(Config {
+        win_level: 11,
+        ..Default::default()
+    });
+    env.fund_prize_vault(RADIX_TOKEN, dec!("100")).expect_commit_success();
+    env.admin_call("set_redeem_fee_fraction", args!(dec!("0.05"))).expect_commit_success();
+    let ticket_id = env.win_ticket(dec!("1"));
+
+    // the player gets the prize minus 5%, the jackpot is paid in full
+    let jackpot: Decimal = env.read("jackpot_balance", args!());
+    let fees: Decimal = env.read("fee_balance", args!());
+    let balance_before = env.xrd_balance();
+    let receipt = env.redeem_prize(&ticket_id);
+    receipt.expect_commit_success();
+    assert_eq!(env.xrd_balance(), balance_before + dec!("4.75") + jackpot);
+
+    let events: Vec<PrizeRedeemed> = env.events(&receipt, "PrizeRedeemed");
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].ticket_id, ticket_id);
+    assert_eq!(
+        (events[0].gross, events[0].fee, events[0].net),
+        (dec!("5"), dec!("0.25"), dec!("4.75"))
+    );
+
+    // the fee goes to the fee vault, to be collected by the operator
+    assert_eq!(env.read::<Decimal>("prize_vault_balance", args!()), dec!("95"));
+    assert_eq!(env.read::<Decimal>("fee_balance", args!()), fees + dec!("0.25"));
+    assert_eq!(env.read::<Decimal>("total_prizes_paid", args!()), dec!("4.75"));
+
+    let receipt = env.admin_call("set_redeem_fee_fraction", args!(dec!("1.5")));
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("The redeem fee fraction must be between 0 and 1")
+    });
+}
+
+#[test]
+fn test_double_prize_window() {
+    // a new ticket starts at level 10, so a win level of 11 is reached quickly
+    let mut env = TestEnv::with_config(Config {
+        win_level: 11,
+        ..Default::default()
+    });
+    env.fund_prize_vault(RADIX_TOKEN, dec!("15")).expect_commit_success();
+    env.test_runner.set_current_epoch(10);
+    env.admin_call("set_double_prize_until_epoch", args!(12u64)).expect_commit_success();
+
+    // inside the window the prize is doubled
+    let ticket_id = env.win_ticket(dec!("1"));
+    let jackpot: Decimal = env.read("jackpot_balance", args!());
+    let balance_before = env.xrd_balance();
+    let receipt = env.redeem_prize(&ticket_id);
+    receipt.expect_commit_success();
+    assert_eq!(env.xrd_balance(), balance_before + dec!("10") + jackpot);
+    assert!(env.events::<PrizeRedeemed>(&receipt, "PrizeRedeemed")[0].doubled);
+
+    // the 5 left in the prize vault can't pay a double prize, the single prize is paid
+    let ticket_id = env.win_ticket(dec!("1"));
+    let receipt = env.redeem_prize(&ticket_id);
+    receipt.expect_commit_success();
+    let redeemed: Vec<PrizeRedeemed> = env.events(&receipt, "PrizeRedeemed");
+    assert_eq!((redeemed[0].gross, redeemed[0].doubled), (dec!("5"), false));
+
+    // outside the window the single prize is paid
+    env.fund_prize_vault(RADIX_TOKEN, dec!("20")).expect_commit_success();
+    env.test_runner.set_current_epoch(12);
+    let ticket_id = env.win_ticket(dec!("1"));
+    let receipt = env.redeem_prize(&ticket_id);
+    receipt.expect_commit_success();
+    let redeemed: Vec<PrizeRedeemed> = env.events(&receipt, "PrizeRedeemed");
+    assert_eq!((redeemed[0].gross, redeemed[0].doubled), (dec!("5"), false));
+}
+
+#[test]
+fn test_redeem_without_matching_tier() {
+    let mut env = TestEnv::new();
+    env.fund_prize_vault(RADIX_TOKEN, dec!("100")).expect_commit_success();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+
+    let receipt = env.redeem_prize(&ticket_id);
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("Level 10 does not match a prize tier")
+    });
+}
+
+#[test]
+fn test_prize_tier_above_win_level() {
+    let (_, _, _, _, receipt) = instantiate(Config {
+        prize_tiers: vec![(30, dec!("2"))],
+        ..Default::default()
+    });
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("Prize tier level 30 must be between 0 and the win level 25")
+    });
+}
+
+#[test]
+fn test_jackpot_grows_and_is_paid() {
+    let mut env = TestEnv::with_config(Config {
+        win_level: 11,
+        ..Default::default()
+    });
+    env.fund_prize_vault(RADIX_TOKEN, dec!("100")).expect_commit_success();
+
+    for nr_tickets in 1..=3 {
+        env.buy_ticket(dec!("1")).expect_commit_success();
+        let jackpot: Decimal = env.read("jackpot_balance", args!());
+        assert_eq!(jackpot, dec!("0.1") * nr_tickets);
+    }
+    // the rest of the buy-in goes to the main wallet
+    assert_eq!(env.read::<Decimal>("vault_balance", args!()), dec!("2.7"));
+
+    let ticket_id = env.win_ticket(dec!("1"));
+    let jackpot: Decimal = env.read("jackpot_balance", args!());
+    let balance_before = env.xrd_balance();
+    env.redeem_prize(&ticket_id).expect_commit_success();
+
+    assert_eq!(env.xrd_balance(), balance_before + dec!("5") + jackpot);
+    assert_eq!(env.read::<Decimal>("jackpot_balance", args!()), dec!("0"));
+}
+
+#[test]
+fn test_staked_round_multiplies_delta() {
+    let mut env = TestEnv::new();
+    let mut saw_win = false;
+    let mut saw_bust = false;
+
+    while !(saw_win && saw_bust) {
+        assert!(env.minted < 50, "No 3x win and 3x bust after 50 tickets");
+        env.buy_ticket(dec!("1")).expect_commit_success();
+        let ticket_id = env.last_ticket_id();
+
+        loop {
+            let receipt = env.play_round_staked(&ticket_id, dec!("2"), 3);
+            if !receipt.is_commit_success() {
+                break;
+            }
+            let event = env.events::<RoundPlayed>(&receipt, "RoundPlayed").remove(0);
+            let diff_of_dice = 3 * (event.player_die - event.house_die);
+            let expected = (event.old_level + diff_of_dice).clamp(0, 25);
+            assert_eq!(event.new_level, expected);
+
+            saw_win |= diff_of_dice > 0;
+            saw_bust |= expected == 0 && event.old_level + diff_of_dice < 0;
+        }
+    }
+}
+
+#[test]
+fn test_staked_round_multiplier_out_of_range() {
+    let mut env = TestEnv::new();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+
+    let receipt = env.play_round_staked(&ticket_id, dec!("3"), 4);
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("The multiplier must be between 1 and 3")
+    });
+}
+
+#[test]
+fn test_get_ticket_status() {
+    let mut env = TestEnv::new();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+
+    let status: (i8, String) = env.read("get_ticket_status", args!(ticket_id));
+    assert_eq!(status, (10, "New Ticket, no play history".to_string()));
+}
+
+#[test]
+fn test_underfunded_prize_emits_event() {
+    let mut env = TestEnv::with_config(Config {
+        prize_tiers: vec![(10, dec!("50"))],
+        ..Default::default()
+    });
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+
+    let receipt = env.redeem_prize(&ticket_id);
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("Not enough funds in the vault to pay prize money")
+    });
+
+    let events: Vec<PrizeUnderfunded> = env.events(&receipt, "PrizeUnderfunded");
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].ticket_id, ticket_id);
+    assert_eq!(events[0].required, dec!("50"));
+    assert_eq!(events[0].available, dec!("0"));
+}
+
+#[test]
+fn test_buy_tickets_batch() {
+    let mut env = TestEnv::with_config(Config {
+        ticket_price: dec!("2"),
+        ..Default::default()
+    });
+    let balance_before = env.xrd_balance();
+
+    env.buy_tickets(5, dec!("12")).expect_commit_success();
+
+    assert_eq!(env.xrd_balance(), balance_before - dec!("10"));
+    let nr_tickets = env
+        .test_runner
+        .account_balance(env.account, env.ticket)
+        .unwrap();
+    assert_eq!(nr_tickets, dec!("5"));
+}
+
+#[test]
+fn test_buy_tickets_not_enough_xrd() {
+    let mut env = TestEnv::new();
+
+    let receipt = env.buy_tickets(5, dec!("4"));
+    receipt.expect_specific_failure(|e| format!("{:?}", e).contains("Not enough XRD supplied"));
+}
+
+#[test]
+fn test_total_tickets_minted() {
+    let mut env = TestEnv::new();
+    assert_eq!(env.read::<u64>("total_tickets_minted", args!()), 0);
+
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    env.buy_tickets(3, dec!("3")).expect_commit_success();
+    env.admin_call("admin_ticket", args!()).expect_commit_success();
+
+    assert_eq!(env.read::<u64>("total_tickets_minted", args!()), 5);
+}
+
+#[test]
+fn test_custom_reinit_discount() {
+    let mut env = TestEnv::with_config(Config {
+        reinit_discount: dec!("0.5"),
+        ..Default::default()
+    });
+    let ticket_id = env.bust_ticket(dec!("1"));
+
+    let balance_before = env.xrd_balance();
+    env.reinit_ticket(&ticket_id, dec!("1")).expect_commit_success();
+    assert_eq!(env.xrd_balance(), balance_before - dec!("0.5"));
+}
+
+#[test]
+fn test_set_reinit_discount() {
+    let mut env = TestEnv::new();
+    env.admin_call("set_reinit_discount", args!(dec!("0.75")))
+        .expect_commit_success();
+    let ticket_id = env.bust_ticket(dec!("1"));
+
+    let balance_before = env.xrd_balance();
+    env.reinit_ticket(&ticket_id, dec!("1")).expect_commit_success();
+    assert_eq!(env.xrd_balance(), balance_before - dec!("0.75"));
+}
+
+#[test]
+fn test_set_reinit_discount_out_of_range() {
+    let mut env = TestEnv::new();
+
+    for factor in [dec!("0"), dec!("1.1")] {
+        let receipt = env.admin_call("set_reinit_discount", args!(factor));
+        receipt.expect_specific_failure(|e| {
+            format!("{:?}", e).contains("The reinit discount must be greater than 0 and at most 1")
+        });
+    }
+}
+
+#[test]
+fn test_ticket_win_loss_record() {
+    let mut env = TestEnv::new();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+    assert_eq!(
+        env.read::<(u32, u16, u16)>("get_ticket_record", args!(ticket_id.clone())),
+        (0, 0, 0)
+    );
+
+    let (mut rounds, mut wins, mut losses) = (0u32, 0u16, 0u16);
+    loop {
+        let receipt = env.play_round(&ticket_id);
+        if !receipt.is_commit_success() {
+            break;
+        }
+        let event = env.events::<RoundPlayed>(&receipt, "RoundPlayed").remove(0);
+        rounds += 1;
+        if event.player_die > event.house_die {
+            wins += 1;
+        } else if event.player_die < event.house_die {
+            losses += 1;
+        }
+    }
+
+    assert_eq!(
+        env.read::<(u32, u16, u16)>("get_ticket_record", args!(ticket_id)),
+        (rounds, wins, losses)
+    );
+}
+
+#[test]
+fn test_mint_and_burn_events() {
+    let mut env = TestEnv::new();
+
+    let receipt = env.buy_ticket(dec!("1"));
+    receipt.expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+    let minted: Vec<TicketMinted> = env.events(&receipt, "TicketMinted");
+    assert_eq!(minted.len(), 1);
+    assert_eq!(minted[0].ticket_id, ticket_id);
+
+    let receipt = env.burn_ticket(&ticket_id);
+    receipt.expect_commit_success();
+    let burned: Vec<TicketBurned> = env.events(&receipt, "TicketBurned");
+    assert_eq!(burned.len(), 1);
+    assert_eq!(burned[0].ticket_id, ticket_id);
+}
+
+#[test]
+fn test_ticket_purchased_event() {
+    let mut env = TestEnv::with_config(Config {
+        ticket_price: dec!("2"),
+        ..Default::default()
+    });
+    let balance_before = env.xrd_balance();
+
+    let receipt = env.buy_ticket(dec!("5"));
+    receipt.expect_commit_success();
+    let purchased: Vec<TicketPurchased> = env.events(&receipt, "TicketPurchased");
+    assert_eq!(purchased.len(), 1);
+    assert_eq!(purchased[0].price_paid, dec!("2"));
+
+    // the NFT bucket holds the ticket of the event, the change bucket the remaining 3 XRD
+    let ticket_id = purchased[0].ticket_id.clone();
+    assert_eq!(ticket_id, env.last_ticket_id());
+    env.play_round(&ticket_id).expect_commit_success();
+    assert_eq!(env.xrd_balance(), balance_before - dec!("2"));
+}
+
+#[test]
+fn test_cooldown_between_rounds() {
+    let mut env = TestEnv::new();
+    env.test_runner.set_current_epoch(10);
+    env.admin_call("set_cooldown_epochs", args!(5u64))
+        .expect_commit_success();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+
+    // one round from level 10 can't finish the ticket
+    env.play_round(&ticket_id).expect_commit_success();
+
+    env.test_runner.set_current_epoch(14);
+    let receipt = env.play_round(&ticket_id);
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("Ticket is cooling down, next round can be played in epoch 15")
+    });
+
+    env.test_runner.set_current_epoch(15);
+    env.play_round(&ticket_id).expect_commit_success();
+}
+
+#[test]
+fn test_play_rounds_stops_at_cooldown() {
+    let mut env = TestEnv::new();
+    env.test_runner.set_current_epoch(10);
+    env.admin_call("set_cooldown_epochs", args!(5u64))
+        .expect_commit_success();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+
+    // only the first round is played and kept, the call isn't reverted by the cooldown
+    let receipt = env.play_rounds(&ticket_id, 5);
+    receipt.expect_commit_success();
+    assert_eq!(env.events::<RoundPlayed>(&receipt, "RoundPlayed").len(), 1);
+
+    let receipt = env.play_rounds(&ticket_id, 5);
+    receipt.expect_specific_failure(|e| format!("{:?}", e).contains("Ticket is cooling down"));
+
+    env.test_runner.set_current_epoch(15);
+    let receipt = env.play_rounds(&ticket_id, 5);
+    receipt.expect_commit_success();
+    assert_eq!(env.events::<RoundPlayed>(&receipt, "RoundPlayed").len(), 1);
+}
+
+#[test]
+fn test_prize_in_custom_token() {
+    let mut env = TestEnv::with_config(Config {
+        win_level: 11,
+        reward_token_prize: true,
+        ..Default::default()
+    });
+    let reward_token = env.reward_token;
+    env.fund_prize_vault(reward_token, dec!("100")).expect_commit_success();
+    assert_eq!(env.read::<Decimal>("prize_vault_balance", args!()), dec!("100"));
+
+    // buy-ins are still collected in XRD
+    let ticket_id = env.win_ticket(dec!("1"));
+    assert_eq!(
+        env.read::<Decimal>("vault_balance", args!()),
+        dec!("0.9") * env.minted
+    );
+
+    env.redeem_prize(&ticket_id).expect_commit_success();
+    let reward_balance = env
+        .test_runner
+        .account_balance(env.account, reward_token)
+        .unwrap();
+    assert_eq!(reward_balance, dec!("905"));
+    assert_eq!(env.read::<Decimal>("prize_vault_balance", args!()), dec!("95"));
+}
+
+#[test]
+fn test_redeem_fee_in_custom_token() {
+    let mut env = TestEnv::with_config(Config {
+        win_level: 11,
+        reward_token_prize: true,
+        ..Default::default()
+    });
+    let reward_token = env.reward_token;
+    env.fund_prize_vault(reward_token, dec!("100")).expect_commit_success();
+    env.admin_call("set_redeem_fee_fraction", args!(dec!("0.1"))).expect_commit_success();
+    let ticket_id = env.win_ticket(dec!("1"));
+    let fees: Decimal = env.read("fee_balance", args!());
+    env.redeem_prize(&ticket_id).expect_commit_success();
+
+    // the fee can't go in the XRD fee vault, it is kept apart from the prize vault
+    assert_eq!(env.read::<Decimal>("prize_vault_balance", args!()), dec!("95"));
+    assert_eq!(env.read::<Decimal>("prize_fee_balance", args!()), dec!("0.5"));
+    assert_eq!(env.read::<Decimal>("fee_balance", args!()), fees);
+
+    env.admin_call("collect_prize_fees", args!()).expect_commit_success();
+    let reward_balance = env.test_runner.account_balance(env.account, reward_token);
+    assert_eq!(reward_balance, Some(dec!("905")));
+    assert_eq!(env.read::<Decimal>("prize_fee_balance", args!()), dec!("0"));
+}
+
+#[test]
+fn test_fund_prize_vault_wrong_resource() {
+    let mut env = TestEnv::with_config(Config {
+        reward_token_prize: true,
+        ..Default::default()
+    });
+
+    let receipt = env.fund_prize_vault(RADIX_TOKEN, dec!("100"));
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("The prize vault can only be funded with the prize resource")
+    });
+}
+
+#[test]
+fn test_refund_dead_ticket() {
+    let mut env = TestEnv::new();
+    env.admin_call("set_dead_ticket_refund", args!(dec!("0.2")))
+        .expect_commit_success();
+    let ticket_id = env.bust_ticket(dec!("1"));
+    let nr_tickets = env.test_runner.account_balance(env.account, env.ticket).unwrap();
+    let balance_before = env.xrd_balance();
+
+    let receipt = env.refund_dead_ticket(&ticket_id);
+    receipt.expect_commit_success();
+
+    assert_eq!(env.xrd_balance(), balance_before + dec!("0.2"));
+    assert_eq!(
+        env.test_runner.account_balance(env.account, env.ticket).unwrap(),
+        nr_tickets - dec!("1")
+    );
+    let burned: Vec<TicketBurned> = env.events(&receipt, "TicketBurned");
+    assert_eq!(burned[0].ticket_id, ticket_id);
+}
+
+#[test]
+fn test_refund_dead_ticket_limits() {
+    let mut env = TestEnv::new();
+    let receipt = env.admin_call("set_dead_ticket_refund", args!(dec!("1.5")));
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("The dead ticket refund must be between 0 and the ticket price")
+    });
+
+    // the main wallet holds 1.8 XRD after two sales, with a reserve of 1 only 0.8 can be refunded
+    env.admin_call("set_dead_ticket_refund", args!(dec!("1"))).expect_commit_success();
+    env.buy_tickets(2, dec!("2")).expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+    env.set_ticket_level(&ticket_id, 0).expect_commit_success();
+    env.admin_call("set_min_reserve", args!(dec!("1"))).expect_commit_success();
+    let receipt = env.refund_dead_ticket(&ticket_id);
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("Not enough funds above the minimum reserve to pay the refund")
+    });
+
+    env.admin_call("set_min_reserve", args!(dec!("0"))).expect_commit_success();
+    env.admin_call("set_paused", args!(true)).expect_commit_success();
+    let receipt = env.refund_dead_ticket(&ticket_id);
+    receipt.expect_specific_failure(|e| format!("{:?}", e).contains("Game is paused"));
+    env.admin_call("set_paused", args!(false)).expect_commit_success();
+    env.refund_dead_ticket(&ticket_id).expect_commit_success();
+}
+
+#[test]
+fn test_refund_playable_ticket() {
+    let mut env = TestEnv::new();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+
+    let receipt = env.refund_dead_ticket(&ticket_id);
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("Level not 0, Ticket still playable")
+    });
+}
+
+#[test]
+fn test_is_redeemable() {
+    let mut env = TestEnv::with_config(Config {
+        win_level: 11,
+        ..Default::default()
+    });
+    let win_ticket = env.win_ticket(dec!("1"));
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let new_ticket = env.last_ticket_id();
+
+    assert!(env.read::<bool>("is_redeemable", args!(win_ticket)));
+    assert!(!env.read::<bool>("is_redeemable", args!(new_ticket)));
+}
+
+#[test]
+fn test_loaded_die_favors_six() {
+    let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+    let nr_rolls = 70_000;
+    let mut faces = [0u32; 6];
+    for _ in 0..nr_rolls {
+        faces[(roll_die(|| rng.next(), 6, true) - 1) as usize] += 1;
+    }
+
+    // faces 1 to 5 are expected 10000 times and face 6 20000 times, allow 5% deviation
+    for count in &faces[..5] {
+        assert!((9_500..=10_500).contains(count), "face count {} out of band", count);
+    }
+    assert!((19_000..=21_000).contains(&faces[5]), "six count {} out of band", faces[5]);
+}
+
+// Play many throws in the given mode with ties rolled again, returns (player wins, house wins)
+fn count_throw_outcomes(dice_mode: u8) -> (u32, u32) {
+    let mut rng = XorShift(0x9e37_79b9_7f4a_7c15);
+    let (mut player_wins, mut house_wins) = (0, 0);
+    for _ in 0..35_000 {
+        let (house_die, player_die) =
+            throw_dice(|loaded| roll_die(|| rng.next(), 6, loaded), dice_mode);
+        if player_die > house_die {
+            player_wins += 1;
+        } else if player_die < house_die {
+            house_wins += 1;
+        }
+    }
+    (player_wins, house_wins)
+}
+
+#[test]
+fn test_throw_from_raws_distribution() {
+    // the dice thrown from the raws of a round follow the dice mode odds,
+    // every (house, player) pair of a fair d6 is about equally likely
+    let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+    for rng_strategy in [RNG_STRATEGY_REJECTION, RNG_STRATEGY_MODULO] {
+        let mut pairs = [[0u32; 6]; 6];
+        let (mut player_wins, mut house_wins) = (0, 0);
+        for _ in 0..36_000 {
+            let raws: Vec<u128> = (0..64).map(|_| rng.next()).collect();
+            let (house_die, player_die) =
+                throw_from_raws(&raws, 6, DICE_MODE_FAIR, false, rng_strategy);
+            pairs[house_die as usize - 1][player_die as usize - 1] += 1;
+            if player_die > house_die {
+                player_wins += 1;
+            } else if player_die < house_die {
+                house_wins += 1;
+            }
+        }
+        assert!((17_300..=18_700).contains(&player_wins), "player wins {}", player_wins);
+        assert!((17_300..=18_700).contains(&house_wins), "house wins {}", house_wins);
+        // ties are rolled again, the 30 other pairs come up 1200 times each on average
+        for (house, row) in pairs.iter().enumerate() {
+            for (player, count) in row.iter().enumerate() {
+                if house != player {
+                    assert!((1_000..=1_400).contains(count), "pair {} {}", house + 1, player + 1);
+                }
+            }
+        }
+    }
+
+    let mut rng = XorShift(0x9e37_79b9_7f4a_7c15);
+    let mut player_wins = 0;
+    for _ in 0..35_000 {
+        let raws: Vec<u128> = (0..64).map(|_| rng.next()).collect();
+        let (house_die, player_die) =
+            throw_from_raws(&raws, 6, DICE_MODE_PLAYER_FAVORED, false, RNG_STRATEGY_REJECTION);
+        if player_die > house_die {
+            player_wins += 1;
+        }
+    }
+    // the player is expected to win 4/7 of the rounds, 20000 out of 35000
+    assert!((19_300..=20_700).contains(&player_wins), "player wins {}", player_wins);
+}
+
+#[test]
+fn test_dice_modes_bias_direction() {
+    // the fair mode is expected to split the rounds evenly
+    let (player_wins, house_wins) = count_throw_outcomes(DICE_MODE_FAIR);
+    assert!((16_800..=18_200).contains(&player_wins), "player wins {}", player_wins);
+    assert!((16_800..=18_200).contains(&house_wins), "house wins {}", house_wins);
+
+    // the favored side is expected to win 4/7 of the rounds, 20000 out of 35000
+    let (player_wins, house_wins) = count_throw_outcomes(DICE_MODE_PLAYER_FAVORED);
+    assert!((19_300..=20_700).contains(&player_wins), "player wins {}", player_wins);
+    assert!(player_wins > house_wins);
+
+    let (player_wins, house_wins) = count_throw_outcomes(DICE_MODE_HOUSE_FAVORED);
+    assert!((19_300..=20_700).contains(&house_wins), "house wins {}", house_wins);
+    assert!(house_wins > player_wins);
+}
+
+#[test]
+fn test_invalid_dice_mode() {
+    let (_, _, _, _, receipt) = instantiate(Config {
+        dice_mode: 3,
+        ..Default::default()
+    });
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("The dice mode must be 0 (fair), 1 (player favored) or 2")
+    });
+}
+
+#[test]
+fn test_fees_are_split_from_ticket_sales() {
+    let mut env = TestEnv::new();
+    env.admin_call("set_fee_fraction", args!(dec!("0.2")))
+        .expect_commit_success();
+
+    env.buy_tickets(5, dec!("5")).expect_commit_success();
+    assert_eq!(env.read::<Decimal>("jackpot_balance", args!()), dec!("0.5"));
+    assert_eq!(env.read::<Decimal>("fee_balance", args!()), dec!("1"));
+    assert_eq!(env.read::<Decimal>("vault_balance", args!()), dec!("3.5"));
+
+    // withdrawal_all only empties the main wallet
+    let balance_before = env.xrd_balance();
+    env.admin_call("withdrawal_all", args!()).expect_commit_success();
+    assert_eq!(env.xrd_balance(), balance_before + dec!("3.5"));
+    assert_eq!(env.read::<Decimal>("fee_balance", args!()), dec!("1"));
+
+    let balance_before = env.xrd_balance();
+    env.admin_call("collect_fees", args!()).expect_commit_success();
+    assert_eq!(env.xrd_balance(), balance_before + dec!("1"));
+    assert_eq!(env.read::<Decimal>("fee_balance", args!()), dec!("0"));
+}
+
+#[test]
+fn test_fee_fraction_out_of_range() {
+    let mut env = TestEnv::new();
+
+    let receipt = env.admin_call("set_fee_fraction", args!(dec!("0.95")));
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("The fee fraction must be between 0 and 0.9")
+    });
+}
+
+#[test]
+fn test_ticket_price_bounds() {
+    let mut env = TestEnv::new();
+    env.admin_call("set_ticket_price", args!(dec!("2"))).expect_commit_success();
+    let receipt = env.buy_ticket(dec!("1.5"));
+    receipt.expect_specific_failure(|e| format!("{:?}", e).contains("Not enough XRD supplied"));
+    env.buy_ticket(dec!("2")).expect_commit_success();
+
+    for price in [dec!("0.001"), dec!("2000000")] {
+        let receipt = env.admin_call("set_ticket_price", args!(price));
+        receipt.expect_specific_failure(|e| {
+            format!("{:?}", e).contains("The ticket price must be between 0.01 and 1000000")
+        });
+    }
+
+    let (_, _, _, _, receipt) = instantiate(Config {
+        ticket_price: dec!("0.001"),
+        ..Default::default()
+    });
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("The ticket price must be between 0.01 and 1000000, got 0.001")
+    });
+}
+
+#[test]
+fn test_max_tickets_cap() {
+    let mut env = TestEnv::with_config(Config {
+        max_tickets: 3,
+        ..Default::default()
+    });
+    env.buy_tickets(2, dec!("2")).expect_commit_success();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+
+    let receipt = env.buy_ticket(dec!("1"));
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("All 3 tickets have been minted, no more tickets available")
+    });
+    let receipt = env.admin_call("admin_ticket", args!());
+    receipt
+        .expect_specific_failure(|e| format!("{:?}", e).contains("All 3 tickets have been minted"));
+}
+
+#[test]
+fn test_remaining_mints() {
+    let mut env = TestEnv::new();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    assert_eq!(env.read::<Option<u64>>("remaining_mints", args!()), None);
+
+    let mut env = TestEnv::with_config(Config {
+        max_tickets: 3,
+        ..Default::default()
+    });
+    assert_eq!(env.read::<Option<u64>>("remaining_mints", args!()), Some(3));
+    env.buy_tickets(2, dec!("2")).expect_commit_success();
+    assert_eq!(env.read::<Option<u64>>("remaining_mints", args!()), Some(1));
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    assert_eq!(env.read::<Option<u64>>("remaining_mints", args!()), Some(0));
+}
+
+#[test]
+fn test_play_round_with_bucket() {
+    let mut env = TestEnv::new();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+
+    let receipt = env.play_round_with_bucket(&ticket_id);
+    receipt.expect_commit_success();
+
+    let event = env.events::<RoundPlayed>(&receipt, "RoundPlayed").remove(0);
+    assert_eq!(event.ticket_id, ticket_id);
+    let status: (i8, String) = env.read("get_ticket_status", args!(ticket_id));
+    assert_eq!(status.0, event.new_level);
+    // the ticket is back in the account
+    assert_eq!(
+        env.test_runner.account_balance(env.account, env.ticket).unwrap(),
+        dec!("1")
+    );
+}
+
+#[test]
+fn test_set_ticket_level() {
+    let mut env = TestEnv::new();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+
+    let receipt = env.set_ticket_level(&ticket_id, 20);
+    receipt.expect_commit_success();
+
+    let status: (i8, String) = env.read("get_ticket_status", args!(ticket_id.clone()));
+    assert_eq!(status, (20, "Adjusted by admin".to_string()));
+    let events: Vec<LevelAdjusted> = env.events(&receipt, "LevelAdjusted");
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].ticket_id, ticket_id);
+    assert_eq!(events[0].new_level, 20);
+}
+
+#[test]
+fn test_set_ticket_level_out_of_range() {
+    let mut env = TestEnv::new();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+
+    for level in [-1, 25, 26] {
+        let receipt = env.set_ticket_level(&ticket_id, level);
+        receipt.expect_specific_failure(|e| {
+            format!("{:?}", e).contains("The level must be between 0 and below the win level 25")
+        });
+    }
+}
+
+#[test]
+fn test_recent_throws_keep_last_five() {
+    let mut env = TestEnv::with_config(Config {
+        win_level: 100,
+        ..Default::default()
+    });
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+    // 7 rounds from level 50 can't finish the ticket
+    env.set_ticket_level(&ticket_id, 50).expect_commit_success();
+
+    let mut throws = Vec::new();
+    for _ in 0..7 {
+        let receipt = env.play_round(&ticket_id);
+        receipt.expect_commit_success();
+        let event = env.events::<RoundPlayed>(&receipt, "RoundPlayed").remove(0);
+        throws.push(format!(
+            "House {}, Player {}, New Lvl {}({:+})",
+            event.house_die,
+            event.player_die,
+            event.new_level,
+            event.player_die - event.house_die
+        ));
+    }
+
+    let recent: Vec<String> = env.read("get_recent_throws", args!(ticket_id));
+    assert_eq!(recent.len(), RECENT_THROWS);
+    assert_eq!(recent, throws[2..].to_vec());
+}
+
+#[test]
+fn test_withdrawal_needs_two_admin_badges() {
+    let mut env = TestEnv::new();
+    env.deposit(dec!("100"), dec!("100")).expect_commit_success();
+
+    for method in ["withdrawal_all", "withdraw"] {
+        let args = if method == "withdraw" {
+            args!(dec!("10"))
+        } else {
+            args!()
+        };
+        let receipt = env.admin_call_with_badges(dec!("1"), method, args);
+        receipt.expect_specific_failure(|e| format!("{:?}", e).contains("Unauthorized"));
+    }
+    // a single badge is still enough for the other admin functions
+    env.admin_call_with_badges(dec!("1"), "set_paused", args!(false))
+        .expect_commit_success();
+
+    let balance_before = env.xrd_balance();
+    env.admin_call_with_badges(dec!("2"), "withdrawal_all", args!())
+        .expect_commit_success();
+    assert_eq!(env.xrd_balance(), balance_before + dec!("100"));
+}
+
+#[test]
+fn test_ticket_resource_address() {
+    let mut env = TestEnv::new();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+
+    let ticket: ResourceAddress = env.read("ticket_resource_address", args!());
+    assert_eq!(ticket, env.ticket);
+    // the bought ticket is of that resource
+    assert_eq!(
+        env.test_runner.account_balance(env.account, ticket).unwrap(),
+        dec!("1")
+    );
+}
+
+#[test]
+fn test_roll_die_covers_all_faces() {
+    let mut rng = XorShift(0x9e37_79b9_7f4a_7c15);
+    for faces in MIN_DICE_FACES..=MAX_DICE_FACES {
+        let mut seen = vec![false; faces as usize];
+        for _ in 0..2_000 {
+            let die = roll_die(|| rng.next(), faces, false);
+            assert!((1..=faces as i8).contains(&die), "d{} rolled {}", faces, die);
+            seen[(die - 1) as usize] = true;
+        }
+        assert!(seen.iter().all(|s| *s), "d{} missed a face", faces);
+
+        // the extra value of a loaded die is its highest face
+        for _ in 0..200 {
+            let die = roll_die(|| rng.next(), faces, true);
+            assert!((1..=faces as i8).contains(&die), "loaded d{} rolled {}", faces, die);
+        }
+    }
+}
+
+// Play rounds on a fresh ticket of a fair instance with the given dice, checking every throw
+fn assert_rounds_in_range(dice_faces: u8) {
+    let mut env = TestEnv::with_config(Config {
+        dice_faces,
+        ..Default::default()
+    });
+    for _ in 0..5 {
+        env.buy_ticket(dec!("1")).expect_commit_success();
+        let ticket_id = env.last_ticket_id();
+        let receipt = env.play_round(&ticket_id);
+        receipt.expect_commit_success();
+        for event in env.events::<RoundPlayed>(&receipt, "RoundPlayed") {
+            assert!((1..=dice_faces as i8).contains(&event.house_die));
+            assert!((1..=dice_faces as i8).contains(&event.player_die));
+            assert!((0..=25).contains(&event.new_level));
+        }
+    }
+}
+
+#[test]
+fn test_d6_instance_rolls_in_range() {
+    assert_rounds_in_range(6);
+}
+
+#[test]
+fn test_d20_instance_rolls_in_range() {
+    assert_rounds_in_range(20);
+}
+
+#[test]
+fn test_invalid_dice_faces() {
+    for dice_faces in [1, 21] {
+        let (_, _, _, _, receipt) = instantiate(Config {
+            dice_faces,
+            ..Default::default()
+        });
+        receipt.expect_specific_failure(|e| {
+            format!("{:?}", e).contains("The dice must have between 2 and 20 faces")
+        });
+    }
+}
+
+#[test]
+fn test_expected_delta() {
+    // a fair die has no edge, whatever its number of faces
+    assert_eq!(expected_delta(6, DICE_MODE_FAIR), dec!("0"));
+    assert_eq!(expected_delta(20, DICE_MODE_FAIR), dec!("0"));
+
+    // a loaded d6 adds 3/7 to the favored side
+    assert_eq!(expected_delta(6, DICE_MODE_PLAYER_FAVORED), dec!("0.43"));
+    assert_eq!(expected_delta(6, DICE_MODE_HOUSE_FAVORED), dec!("-0.43"));
+
+    let mut env = TestEnv::new();
+    assert_eq!(env.read::<Decimal>("expected_delta", args!()), dec!("0"));
+
+    let mut env = TestEnv::with_config(Config {
+        dice_mode: DICE_MODE_HOUSE_FAVORED,
+        ..Default::default()
+    });
+    assert_eq!(env.read::<Decimal>("expected_delta", args!()), dec!("-0.43"));
+}
+
+#[test]
+fn test_reinit_ticket_emits_event() {
+    let mut env = TestEnv::new();
+    let ticket_id = env.bust_ticket(dec!("1"));
+
+    let receipt = env.reinit_ticket(&ticket_id, dec!("1"));
+    receipt.expect_commit_success();
+    let events = env.events::<TicketReinitialized>(&receipt, "TicketReinitialized");
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].ticket_id, ticket_id);
+}
+
+#[test]
+fn test_reinit_ticket_rejects_other_resource() {
+    let mut env = TestEnv::new();
+    let other_nft = env.test_runner.create_non_fungible_resource(env.account);
+
+    let receipt = env.reinit_ticket_of(other_nft, &NonFungibleLocalId::integer(1), dec!("1"));
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("Invalid proof, the proof is not a RaDiceX ticket")
+    });
+}
+
+#[test]
+fn test_simulate_round_in_range() {
+    let mut env = TestEnv::with_config(Config {
+        dice_faces: 20,
+        ..Default::default()
+    });
+    let balance_before = env.xrd_balance();
+    for _ in 0..5 {
+        let (house_die, player_die, delta) = env.read::<(i8, i8, i8)>("simulate_round", args!());
+        assert!((1..=20).contains(&house_die));
+        assert!((1..=20).contains(&player_die));
+        assert_eq!(delta, player_die - house_die);
+    }
+
+    // practicing is free
+    assert_eq!(env.xrd_balance(), balance_before);
+}
+
+#[test]
+fn test_ticket_age() {
+    let mut env = TestEnv::new();
+    env.test_runner.set_current_epoch(10);
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+    assert_eq!(env.read::<u64>("ticket_age", args!(ticket_id.clone())), 0);
+
+    env.test_runner.set_current_epoch(25);
+    assert_eq!(env.read::<u64>("ticket_age", args!(ticket_id)), 15);
+}
+
+#[test]
+fn test_burn_tickets() {
+    let mut env = TestEnv::new();
+    env.buy_tickets(3, dec!("3")).expect_commit_success();
+    let ticket_ids: BTreeSet<NonFungibleLocalId> = (1..=3).map(|n| env.ticket_id(n)).collect();
+
+    let receipt = env.burn_tickets(ticket_ids.clone());
+    receipt.expect_commit_success();
+    let burned: Vec<TicketBurned> = env.events(&receipt, "TicketBurned");
+    let burned_ids: BTreeSet<NonFungibleLocalId> =
+        burned.into_iter().map(|e| e.ticket_id).collect();
+    assert_eq!(burned_ids, ticket_ids);
+
+    let tickets_left = env
+        .test_runner
+        .account_balance(env.account, env.ticket)
+        .unwrap_or_default();
+    assert_eq!(tickets_left, dec!("0"));
+}
+
+#[test]
+fn test_fuse_tickets() {
+    let mut env = TestEnv::new();
+    env.buy_tickets(3, dec!("3")).expect_commit_success();
+    let (low, high) = (env.ticket_id(1), env.ticket_id(2));
+    env.set_ticket_level(&low, 8).expect_commit_success();
+    env.set_ticket_level(&high, 12).expect_commit_success();
+
+    let receipt = env.fuse_tickets(BTreeSet::from([low, high]));
+    receipt.expect_commit_success();
+    env.minted += 1;
+    let burned: Vec<TicketBurned> = env.events(&receipt, "TicketBurned");
+    assert_eq!(burned.len(), 2);
+
+    // the fused ticket is on the average level, both old tickets are gone
+    let fused = env.last_ticket_id();
+    let (level, last_throw) = env.read::<(i8, String)>("get_ticket_status", args!(fused));
+    assert_eq!((level, last_throw.as_str()), (10, "Fused"));
+    let tickets = env.test_runner.account_balance(env.account, env.ticket);
+    assert_eq!(tickets, Some(dec!("2")));
+
+    let single = env.ticket_id(3);
+    let receipt = env.fuse_tickets(BTreeSet::from([single]));
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("Exactly two (2) tickets can be fused")
+    });
+}
+
+#[test]
+fn test_set_branding() {
+    let mut env = TestEnv::new();
+    let branding = args!(
+        "RaDiceX".to_string(),
+        "https://radicex.example/icon.png".to_string(),
+        "Play dice against the house".to_string()
+    );
+
+    // only an admin can set the branding
+    let manifest = ManifestBuilder::new()
+        .call_method(env.component, "set_branding", branding.clone())
+        .build();
+    env.execute(manifest)
+        .expect_specific_failure(|e| format!("{:?}", e).contains("Unauthorized"));
+
+    env.admin_call("set_branding", branding).expect_commit_success();
+    let address = GlobalAddress::Component(env.component);
+    assert_eq!(env.test_runner.get_metadata(address, "name"), Some("RaDiceX".to_string()));
+    assert_eq!(
+        env.test_runner.get_metadata(address, "icon_url"),
+        Some("https://radicex.example/icon.png".to_string())
+    );
+    assert_eq!(
+        env.test_runner.get_metadata(address, "description"),
+        Some("Play dice against the house".to_string())
+    );
+}
+
+#[test]
+fn test_reinit_ticket_exact_payment() {
+    let mut env = TestEnv::new();
+    let ticket_id = env.bust_ticket(dec!("1"));
+
+    let balance_before = env.xrd_balance();
+    let vault_before = env.read::<Decimal>("vault_balance", args!());
+    // exactly the reinit price, the change bucket comes back empty
+    env.reinit_ticket(&ticket_id, dec!("0.9")).expect_commit_success();
+    assert_eq!(env.xrd_balance(), balance_before - dec!("0.9"));
+    assert_eq!(env.read::<Decimal>("vault_balance", args!()), vault_before + dec!("0.9"));
+
+    env.set_ticket_level(&ticket_id, 0).expect_commit_success();
+    let receipt = env.reinit_ticket(&ticket_id, dec!("0.8"));
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("reinitializing costs 0.9 but 0.8 was supplied")
+    });
+}
+
+#[test]
+fn test_total_prizes_and_wins() {
+    let mut env = TestEnv::with_config(Config {
+        prize_tiers: vec![(10, dec!("1"))],
+        ..Default::default()
+    });
+    env.fund_prize_vault(RADIX_TOKEN, dec!("100")).expect_commit_success();
+    assert_eq!(env.read::<Decimal>("total_prizes_paid", args!()), dec!("0"));
+    assert_eq!(env.read::<u64>("total_wins", args!()), 0);
+
+    // one ticket on the win level and a fresh ticket on the level 10 tier
+    env.buy_tickets(2, dec!("2")).expect_commit_success();
+    let (first, second) = (env.ticket_id(1), env.ticket_id(2));
+    env.play_to_win(&first);
+    env.redeem_prize(&first).expect_commit_success();
+    env.redeem_prize(&second).expect_commit_success();
+
+    assert_eq!(env.read::<Decimal>("total_prizes_paid", args!()), dec!("6"));
+    assert_eq!(env.read::<u64>("total_wins", args!()), 2);
+}
+
+#[test]
+fn test_fund_house() {
+    let mut env = TestEnv::new();
+    let balance_before = env.xrd_balance();
+    env.fund_house(RADIX_TOKEN, dec!("50")).expect_commit_success();
+    assert_eq!(env.read::<Decimal>("vault_balance", args!()), dec!("50"));
+    assert_eq!(env.xrd_balance(), balance_before - dec!("50"));
+
+    let reward_token = env.reward_token;
+    let receipt = env.fund_house(reward_token, dec!("50"));
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("can only be funded with the accepted buy-in resource")
+    });
+}
+
+// Play tickets with play_rounds until one finishes on `final_level`, checking the rounds stop there
+fn assert_play_rounds_stops_on(final_level: i8) {
+    let mut env = TestEnv::new();
+    for _ in 0..50 {
+        env.buy_ticket(dec!("1")).expect_commit_success();
+        let ticket_id = env.last_ticket_id();
+        let receipt = env.play_rounds(&ticket_id, u8::MAX);
+        receipt.expect_commit_success();
+        let level: i8 = receipt.expect_commit().output(2);
+        assert!(level == 0 || level == 25, "Ticket still playable at level {}", level);
+
+        // no round is played after the round which finished the ticket
+        let rounds: Vec<RoundPlayed> = env.events(&receipt, "RoundPlayed");
+        assert_eq!(rounds.last().unwrap().new_level, level);
+        for round in &rounds[..rounds.len() - 1] {
+            assert!(round.new_level > 0 && round.new_level < 25);
+        }
+        if level == final_level {
+            let receipt = env.play_rounds(&ticket_id, 1);
+            receipt.expect_specific_failure(|e| format!("{:?}", e).contains("Ticket not playable"));
+            return;
+        }
+    }
+    panic!("No ticket finished on level {} after 50 tickets", final_level);
+}
+
+#[test]
+fn test_play_rounds_stops_on_win() {
+    assert_play_rounds_stops_on(25);
+}
+
+#[test]
+fn test_play_rounds_stops_on_bust() {
+    assert_play_rounds_stops_on(0);
+}
+
+#[test]
+fn test_custom_start_level() {
+    let mut env = TestEnv::with_config(Config {
+        start_level: 15,
+        ..Default::default()
+    });
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+    let (level, _) = env.read::<(i8, String)>("get_ticket_status", args!(ticket_id.clone()));
+    assert_eq!(level, 15);
+
+    // a reinitialized ticket starts on the start level as well
+    env.set_ticket_level(&ticket_id, 0).expect_commit_success();
+    env.reinit_ticket(&ticket_id, dec!("1")).expect_commit_success();
+    let (level, _) = env.read::<(i8, String)>("get_ticket_status", args!(ticket_id));
+    assert_eq!(level, 15);
+}
+
+#[test]
+fn test_high_score_keeps_peak() {
+    let mut env = TestEnv::new();
+    for _ in 0..20 {
+        env.buy_ticket(dec!("1")).expect_commit_success();
+        let ticket_id = env.last_ticket_id();
+        assert_eq!(env.read::<Option<i8>>("get_high_score", args!(ticket_id.clone())), None);
+
+        // play the ticket until it is finished, the high score follows the peak level
+        let mut peak = 10;
+        let mut dropped_below_peak = false;
+        loop {
+            let receipt = env.play_round(&ticket_id);
+            if !receipt.is_commit_success() {
+                break;
+            }
+            let round: Vec<RoundPlayed> = env.events(&receipt, "RoundPlayed");
+            peak = peak.max(round[0].new_level);
+            dropped_below_peak |= round[0].new_level < peak;
+
+            let high_score = env.read::<Option<i8>>("get_high_score", args!(ticket_id.clone()));
+            assert_eq!(high_score, Some(peak));
+        }
+        if dropped_below_peak {
+            return;
+        }
+    }
+    panic!("No ticket dropped below its peak after 20 tickets");
+}
+
+#[test]
+fn test_mint_promo_ticket() {
+    let mut env = TestEnv::new();
+    env.admin_call("mint_promo_ticket", args!(20i8)).expect_commit_success();
+    env.minted += 1;
+
+    let ticket_id = env.last_ticket_id();
+    let (level, last_throw) =
+        env.read::<(i8, String)>("get_ticket_status", args!(ticket_id.clone()));
+    assert_eq!((level, last_throw.as_str()), (20, "Promo ticket"));
+    assert_eq!(env.read::<(u32, u16, u16)>("get_ticket_record", args!(ticket_id)), (0, 0, 0));
+
+    for level in [25i8, 26] {
+        let receipt = env.admin_call("mint_promo_ticket", args!(level));
+        receipt.expect_specific_failure(|e| {
+            format!("{:?}", e).contains("The level must be between 0 and below the win level 25")
+        });
+    }
+
+    // a single admin badge can't mint a ticket on the win level or a prize tier
+    let receipt = env.admin_call_with_badges(dec!("1"), "mint_promo_ticket", args!(25i8));
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("The level must be between 0 and below the win level 25")
+    });
+    let mut env = TestEnv::with_config(Config {
+        prize_tiers: vec![(15, dec!("1"))],
+        ..Default::default()
+    });
+    for receipt in [
+        env.admin_call_with_badges(dec!("1"), "mint_promo_ticket", args!(15i8)),
+        env.admin_call_with_badges(dec!("1"), "mint_promo_batch", args!(2u32, 15i8)),
+    ] {
+        receipt.expect_specific_failure(|e| {
+            format!("{:?}", e).contains("Level 15 is a prize tier, only reached by playing")
+        });
+    }
+}
+
+#[test]
+fn test_mint_promo_batch() {
+    let mut env = TestEnv::new();
+    env.admin_call("mint_promo_batch", args!(10u32, 15i8)).expect_commit_success();
+    env.minted += 10;
+
+    let tickets = env.test_runner.account_balance(env.account, env.ticket);
+    assert_eq!(tickets, Some(dec!("10")));
+    for n in 1..=10 {
+        let ticket_id = env.ticket_id(n);
+        let (level, last_throw) =
+            env.read::<(i8, String)>("get_ticket_status", args!(ticket_id));
+        assert_eq!((level, last_throw.as_str()), (15, "Promo ticket"));
+    }
+
+    let receipt = env.admin_call("mint_promo_batch", args!(10u32, 26i8));
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("The level must be between 0 and below the win level 25")
+    });
+    let receipt = env.admin_call("mint_promo_batch", args!(0u32, 15i8));
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("At least one (1) promo ticket must be minted")
+    });
+}
+
+#[test]
+fn test_ticket_margins() {
+    let mut env = TestEnv::new();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+    env.set_ticket_level(&ticket_id, 18).expect_commit_success();
+    assert_eq!(env.read::<(i8, i8)>("ticket_margins", args!(ticket_id)), (7, 18));
+}
+
+#[test]
+fn test_reset_season() {
+    let mut env = TestEnv::new();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+    env.admin_call("set_rng_override", args!(Some((1i8, 3i8)))).expect_commit_success();
+    env.play_round(&ticket_id).expect_commit_success();
+    assert_eq!(env.read::<Option<i8>>("get_high_score", args!(ticket_id.clone())), Some(12));
+
+    let receipt = env.admin_call("reset_season", args!());
+    receipt.expect_commit_success();
+    let reset: Vec<SeasonReset> = env.events(&receipt, "SeasonReset");
+    assert_eq!(reset[0].season, 2);
+    assert_eq!(env.read::<u32>("season", args!()), 2);
+
+    // the old score is archived, the new season starts from the level the ticket is on
+    assert_eq!(env.read::<Option<i8>>("get_high_score", args!(ticket_id.clone())), None);
+    let archived = env.read::<Option<i8>>("get_season_high_score", args!(1u32, ticket_id.clone()));
+    assert_eq!(archived, Some(12));
+
+    env.admin_call("set_rng_override", args!(Some((3i8, 1i8)))).expect_commit_success();
+    env.play_round(&ticket_id).expect_commit_success();
+    assert_eq!(env.read::<Option<i8>>("get_high_score", args!(ticket_id)), Some(12));
+}
+
+#[test]
+fn test_only_roll_dice_preview_is_public() {
+    let mut env = TestEnv::with_config(Config {
+        dice_faces: 20,
+        ..Default::default()
+    });
+    for _ in 0..5 {
+        let die = env.read::<i8>("roll_dice_preview", args!());
+        assert!((1..=20).contains(&die));
+    }
+
+    // the internal roller is private and the old modulo roller is gone
+    for (method, args) in [("roll_dice", args!(false)), ("roll_dice_old", args!())] {
+        let manifest = ManifestBuilder::new()
+            .call_method(env.component, method, args)
+            .build();
+        assert!(!env.execute(manifest).is_commit_success(), "{} is callable", method);
+    }
+}
+
+#[test]
+fn test_private_roll_dice_preview() {
+    let mut env = TestEnv::with_config(Config {
+        public_preview: false,
+        ..Default::default()
+    });
+    let manifest = ManifestBuilder::new()
+        .call_method(env.component, "roll_dice_preview", args!())
+        .build();
+    env.execute(manifest).expect_specific_failure(|e| format!("{:?}", e).contains("Unauthorized"));
+}
+
+#[test]
+fn test_start_level_not_below_win_level() {
+    for start_level in [25, 30] {
+        let (_, _, _, _, receipt) = instantiate(Config {
+            start_level,
+            ..Default::default()
+        });
+        receipt.expect_specific_failure(|e| {
+            format!("{:?}", e).contains(&format!(
+                "The start level must be between 0 and the win level 25, got {}",
+                start_level
+            ))
+        });
+    }
+}
+
+// Check the receipt holds exactly one VaultChanged event with the given balance and reason
+fn assert_vault_changed(env: &TestEnv, receipt: &TransactionReceipt, balance: &str, reason: &str) {
+    receipt.expect_commit_success();
+    let events: Vec<VaultChanged> = env.events(receipt, "VaultChanged");
+    assert_eq!(events.len(), 1, "{} events for {}", events.len(), reason);
+    assert_eq!(events[0].reason, reason);
+    assert_eq!(events[0].new_balance, dec!(balance));
+}
+
+#[test]
+fn test_vault_changed_events() {
+    let mut env = TestEnv::new();
+    let receipt = env.deposit(dec!("100"), dec!("100"));
+    assert_vault_changed(&env, &receipt, "100", "deposit");
+    let receipt = env.fund_house(RADIX_TOKEN, dec!("50"));
+    assert_vault_changed(&env, &receipt, "150", "fund_house");
+
+    // 10% of each ticket sale goes to the jackpot
+    let receipt = env.buy_ticket(dec!("1"));
+    assert_vault_changed(&env, &receipt, "150.9", "ticket_sale");
+    let receipt = env.buy_tickets(2, dec!("2"));
+    assert_vault_changed(&env, &receipt, "152.7", "ticket_sale");
+
+    let (first, second, third) = (env.ticket_id(1), env.ticket_id(2), env.ticket_id(3));
+    env.set_ticket_level(&first, 0).expect_commit_success();
+    let receipt = env.reinit_ticket(&first, dec!("1"));
+    assert_vault_changed(&env, &receipt, "153.6", "reinit");
+    let receipt = env.play_round_staked(&first, dec!("1"), 2);
+    assert_vault_changed(&env, &receipt, "154.6", "stake");
+
+    env.admin_call("set_dead_ticket_refund", args!(dec!("0.2"))).expect_commit_success();
+    env.set_ticket_level(&second, 0).expect_commit_success();
+    let receipt = env.refund_dead_ticket(&second);
+    assert_vault_changed(&env, &receipt, "154.4", "dead_ticket_refund");
+
+    let receipt = env.admin_call("withdraw", args!(dec!("4.4")));
+    assert_vault_changed(&env, &receipt, "150", "withdrawal");
+    let receipt = env.admin_call("withdrawal_all", args!());
+    assert_vault_changed(&env, &receipt, "0", "withdrawal");
+
+    // prizes are paid from the prize vault, the main wallet is left alone
+    env.fund_prize_vault(RADIX_TOKEN, dec!("100")).expect_commit_success();
+    env.play_to_win(&third);
+    let receipt = env.redeem_prize(&third);
+    receipt.expect_commit_success();
+    assert!(env.events::<VaultChanged>(&receipt, "VaultChanged").is_empty());
+}
+
+#[test]
+fn test_withdraw_and_deposit_admin_badge() {
+    let mut env = TestEnv::new();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+
+    // like the withdrawals, taking out the internal badge needs 2 admin badges
+    let receipt = env.admin_call_with_badges(dec!("1"), "withdraw_admin_badge", args!());
+    receipt.expect_specific_failure(|e| format!("{:?}", e).contains("Unauthorized"));
+
+    env.admin_call("withdraw_admin_badge", args!()).expect_commit_success();
+    let badges = env.test_runner.account_balance(env.account, env.admin_badge);
+    assert_eq!(badges, Some(dec!("3")));
+
+    // without the internal badge tickets can't be minted or updated
+    let receipt = env.buy_ticket(dec!("1"));
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("The internal admin badge is withdrawn")
+    });
+    let receipt = env.play_round(&ticket_id);
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("The internal admin badge is withdrawn")
+    });
+
+    let manifest = ManifestBuilder::new()
+        .withdraw_from_account_by_amount(env.account, dec!("1"), env.admin_badge)
+        .create_proof_from_account_by_amount(env.account, dec!("2"), env.admin_badge)
+        .take_from_worktop(env.admin_badge, |builder, bucket_id| {
+            builder.call_method(env.component, "deposit_admin_badge", args!(bucket_id))
+        })
+        .build();
+    env.execute(manifest).expect_commit_success();
+
+    env.play_round(&ticket_id).expect_commit_success();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+}
+
+#[test]
+fn test_redeem_cooldown() {
+    let mut env = TestEnv::new();
+    env.fund_prize_vault(RADIX_TOKEN, dec!("100")).expect_commit_success();
+    env.admin_call("set_redeem_cooldown_epochs", args!(5u64)).expect_commit_success();
+    env.buy_tickets(3, dec!("3")).expect_commit_success();
+    let ticket_ids: Vec<NonFungibleLocalId> = (1..=3).map(|n| env.ticket_id(n)).collect();
+    for ticket_id in &ticket_ids {
+        env.set_ticket_level(ticket_id, 25).expect_commit_success();
+    }
+
+    env.test_runner.set_current_epoch(10);
+    env.redeem_prize(&ticket_ids[0]).expect_commit_success();
+
+    // a rapid second redemption is blocked, also for another ticket
+    env.test_runner.set_current_epoch(14);
+    let receipt = env.redeem_prize(&ticket_ids[1]);
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("the next prize can be redeemed in epoch 15")
+    });
+
+    env.test_runner.set_current_epoch(15);
+    env.redeem_prize(&ticket_ids[1]).expect_commit_success();
+    let receipt = env.redeem_prize(&ticket_ids[2]);
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("the next prize can be redeemed in epoch 20")
+    });
+}
+
+#[test]
+fn test_burn_ticket_returns_level() {
+    let mut env = TestEnv::new();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+    env.set_ticket_level(&ticket_id, 7).expect_commit_success();
+
+    let receipt = env.burn_ticket(&ticket_id);
+    receipt.expect_commit_success();
+    let level: i8 = receipt.expect_commit().output(2);
+    assert_eq!(level, 7);
+    let burned: Vec<TicketBurned> = env.events(&receipt, "TicketBurned");
+    assert_eq!(burned[0].level, 7);
+}
+
+#[test]
+fn test_play_round_min() {
+    let mut env = TestEnv::new();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+
+    // from level 10 a single round can't reach level 25, the round is reverted
+    let receipt = env.play_round_min(&ticket_id, 25);
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("below the minimum level 25, the round is reverted")
+    });
+    let (level, _) = env.read::<(i8, String)>("get_ticket_status", args!(ticket_id.clone()));
+    assert_eq!(level, 10);
+    let record = env.read::<(u32, u16, u16)>("get_ticket_record", args!(ticket_id.clone()));
+    assert_eq!(record, (0, 0, 0));
+
+    // every level is accepted with a minimum of 0
+    let receipt = env.play_round_min(&ticket_id, 0);
+    receipt.expect_commit_success();
+    let level: i8 = receipt.expect_commit().output(2);
+    let round: Vec<RoundPlayed> = env.events(&receipt, "RoundPlayed");
+    assert_eq!(round[0].new_level, level);
+}
+
+#[test]
+fn test_ticket_resource_metadata() {
+    let mut env = TestEnv::with_config(Config {
+        ticket_description: Some("Roll your way to 25".to_string()),
+        ticket_icon_url: Some("https://radicex.example/ticket.png".to_string()),
+        ticket_tags: Some(vec!["dice".to_string(), "radix".to_string()]),
+        ..Default::default()
+    });
+    let ticket = GlobalAddress::Resource(env.ticket);
+    let metadata = |env: &mut TestEnv, key: &str| env.test_runner.get_metadata(ticket, key);
+    assert_eq!(metadata(&mut env, "name"), Some("Ticket for RaDiceX".to_string()));
+    assert_eq!(metadata(&mut env, "description"), Some("Roll your way to 25".to_string()));
+    assert_eq!(
+        metadata(&mut env, "icon_url"),
+        Some("https://radicex.example/ticket.png".to_string())
+    );
+    assert_eq!(metadata(&mut env, "tags"), Some("dice,radix".to_string()));
+
+    // without metadata the defaults are used and the icon is left out
+    let mut env = TestEnv::new();
+    let ticket = GlobalAddress::Resource(env.ticket);
+    let tags = env.test_runner.get_metadata(ticket, "tags");
+    assert_eq!(tags, Some("game,dice".to_string()));
+    assert_eq!(env.test_runner.get_metadata(ticket, "icon_url"), None);
+    assert!(env.test_runner.get_metadata(ticket, "description").is_some());
+}
+
+#[test]
+fn test_outstanding_prize_liability() {
+    let mut env = TestEnv::new();
+    env.fund_prize_vault(RADIX_TOKEN, dec!("100")).expect_commit_success();
+    assert_eq!(env.read::<Decimal>("outstanding_prize_liability", args!()), dec!("0"));
+
+    // a ticket played to the win level adds the prize amount
+    let winner = env.win_ticket(dec!("1"));
+    assert_eq!(env.read::<Decimal>("outstanding_prize_liability", args!()), dec!("5"));
+
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let adjusted = env.last_ticket_id();
+    env.play_to_win(&adjusted);
+    assert_eq!(env.read::<Decimal>("outstanding_prize_liability", args!()), dec!("10"));
+
+    // redeeming or burning a ticket on the win level settles its part
+    env.redeem_prize(&winner).expect_commit_success();
+    assert_eq!(env.read::<Decimal>("outstanding_prize_liability", args!()), dec!("5"));
+    env.burn_ticket(&adjusted).expect_commit_success();
+    assert_eq!(env.read::<Decimal>("outstanding_prize_liability", args!()), dec!("0"));
+}
+
+#[test]
+fn test_single_die_step() {
+    for roll in 1..=3 {
+        assert_eq!(single_die_step(roll, 6), -1);
+    }
+    for roll in 4..=6 {
+        assert_eq!(single_die_step(roll, 6), 1);
+    }
+}
+
+#[test]
+fn test_single_die_mode_rounds() {
+    let mut env = TestEnv::with_config(Config {
+        single_die_mode: true,
+        ..Default::default()
+    });
+    assert_eq!(env.read::<Decimal>("expected_delta", args!()), dec!("0"));
+
+    // play until the ticket went both up and down, every round moves a single level
+    let (mut went_up, mut went_down) = (false, false);
+    for _ in 0..20 {
+        env.buy_ticket(dec!("1")).expect_commit_success();
+        let ticket_id = env.last_ticket_id();
+        for _ in 0..5 {
+            let receipt = env.play_round(&ticket_id);
+            receipt.expect_commit_success();
+            let round: Vec<RoundPlayed> = env.events(&receipt, "RoundPlayed");
+            assert_eq!(round[0].house_die, 3);
+            let step = round[0].new_level - round[0].old_level;
+            assert_eq!(step, single_die_step(round[0].player_die, 6));
+            went_up |= step == 1;
+            went_down |= step == -1;
+        }
+        if went_up && went_down {
+            return;
+        }
+    }
+    panic!("The single die never went both up and down");
+}
+
+#[test]
+fn test_single_die_mode_needs_fair_die() {
+    let (_, _, _, _, receipt) = instantiate(Config {
+        single_die_mode: true,
+        dice_mode: DICE_MODE_PLAYER_FAVORED,
+        ..Default::default()
+    });
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("The single die mode can only be played with a fair die")
+    });
+}
+
+#[test]
+fn test_shutdown() {
+    let mut env = TestEnv::new();
+    env.fund_prize_vault(RADIX_TOKEN, dec!("100")).expect_commit_success();
+    env.buy_tickets(10, dec!("10")).expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+
+    // a single admin badge is not enough to wind down the game
+    let receipt = env.admin_call_with_badges(dec!("1"), "shutdown", args!());
+    receipt.expect_specific_failure(|e| format!("{:?}", e).contains("Unauthorized"));
+
+    let balance_before = env.xrd_balance();
+    let receipt = env.admin_call("shutdown", args!());
+    receipt.expect_commit_success();
+    // 9 XRD in the main wallet, 1 XRD in the jackpot and the 100 XRD prizes
+    assert_eq!(env.xrd_balance(), balance_before + dec!("110"));
+    let badges = env.test_runner.account_balance(env.account, env.admin_badge);
+    assert_eq!(badges, Some(dec!("3")));
+
+    assert!(env.read::<bool>("is_paused", args!()));
+    let receipt = env.play_round(&ticket_id);
+    receipt.expect_specific_failure(|e| format!("{:?}", e).contains("Game is paused"));
+}
+
+#[test]
+fn test_shutdown_returns_custom_token_prizes() {
+    let mut env = TestEnv::with_config(Config {
+        reward_token_prize: true,
+        ..Default::default()
+    });
+    let reward_token = env.reward_token;
+    env.fund_prize_vault(reward_token, dec!("100")).expect_commit_success();
+    env.buy_tickets(10, dec!("10")).expect_commit_success();
+
+    let balance_before = env.xrd_balance();
+    env.admin_call("shutdown", args!()).expect_commit_success();
+    // the prizes come back in the reward token, the buy-ins in XRD
+    let reward_balance = env.test_runner.account_balance(env.account, reward_token);
+    assert_eq!(reward_balance, Some(dec!("1000")));
+    assert_eq!(env.xrd_balance(), balance_before + dec!("10"));
+    assert_eq!(env.read::<Decimal>("prize_vault_balance", args!()), dec!("0"));
+}
+
+#[test]
+fn test_empty_buckets_are_rejected() {
+    let mut env = TestEnv::new();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+    env.set_ticket_level(&ticket_id, 0).expect_commit_success();
+
+    let receipts = vec![
+        env.deposit(dec!("1"), dec!("0")),
+        env.buy_ticket(dec!("0")),
+        env.reinit_ticket(&ticket_id, dec!("0")),
+    ];
+    for receipt in receipts {
+        receipt.expect_specific_failure(|e| {
+            format!("{:?}", e).contains("The supplied bucket is empty")
+        });
+    }
+}
+
+#[test]
+fn test_max_delta_clamps_rounds() {
+    let mut env = TestEnv::with_config(Config {
+        win_level: 100,
+        start_level: 50,
+        dice_faces: 20,
+        ..Default::default()
+    });
+    env.admin_call("set_max_delta", args!(2i8)).expect_commit_success();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+
+    // a d20 round easily changes more than 2 levels, each round is clamped to 2
+    for _ in 0..10 {
+        let receipt = env.play_round(&ticket_id);
+        receipt.expect_commit_success();
+        let round: Vec<RoundPlayed> = env.events(&receipt, "RoundPlayed");
+        let step = round[0].new_level - round[0].old_level;
+        let dice_diff = round[0].player_die - round[0].house_die;
+        assert_eq!(step, dice_diff.clamp(-2, 2));
+    }
+
+    let receipt = env.admin_call("set_max_delta", args!(-1i8));
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("The max delta can not be negative")
+    });
+}
+
+#[test]
+fn test_gift_ticket() {
+    let mut env = TestEnv::new();
+    let message = "Happy birthday, good luck!".to_string();
+    env.admin_call("gift_ticket", args!(message.clone())).expect_commit_success();
+    env.minted += 1;
+
+    let ticket_id = env.last_ticket_id();
+    let (level, last_throw) = env.read::<(i8, String)>("get_ticket_status", args!(ticket_id));
+    assert_eq!(level, 10);
+    assert_eq!(last_throw, message);
+
+    let manifest = ManifestBuilder::new()
+        .call_method(env.component, "gift_ticket", args!(message))
+        .build();
+    env.execute(manifest)
+        .expect_specific_failure(|e| format!("{:?}", e).contains("Unauthorized"));
+}
+
+#[test]
+fn test_net_revenue() {
+    let mut env = TestEnv::new();
+    env.fund_prize_vault(RADIX_TOKEN, dec!("100")).expect_commit_success();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    env.buy_tickets(2, dec!("2")).expect_commit_success();
+    let (first, second) = (env.ticket_id(1), env.ticket_id(2));
+
+    env.set_ticket_level(&first, 0).expect_commit_success();
+    env.reinit_ticket(&first, dec!("1")).expect_commit_success();
+    assert_eq!(env.read::<Decimal>("total_buyins", args!()), dec!("3.9"));
+    assert_eq!(env.read::<Decimal>("net_revenue", args!()), dec!("3.9"));
+
+    env.play_to_win(&second);
+    env.redeem_prize(&second).expect_commit_success();
+    assert_eq!(env.read::<Decimal>("net_revenue", args!()), dec!("-1.1"));
+}
+
+#[test]
+fn test_roll_die_fallback_is_bounded() {
+    // all bits set rejects every value of a d6, fair or loaded
+    for loaded in [false, true] {
+        let mut nr_calls = 0u32;
+        let die = roll_die(
+            || {
+                nr_calls += 1;
+                u128::MAX
+            },
+            6,
+            loaded,
+        );
+        assert!((1..=6).contains(&die));
+        assert_eq!(nr_calls, MAX_ROLL_ATTEMPTS as u32);
+    }
+
+    // a working source never reaches the fallback
+    let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+    for _ in 0..1_000 {
+        let mut nr_calls = 0u32;
+        let die = roll_die(
+            || {
+                nr_calls += 1;
+                rng.next()
+            },
+            6,
+            false,
+        );
+        assert!((1..=6).contains(&die));
+        assert!(nr_calls < MAX_ROLL_ATTEMPTS as u32);
+    }
+}
+
+#[test]
+fn test_min_reserve() {
+    let mut env = TestEnv::new();
+    env.deposit(dec!("100"), dec!("100")).expect_commit_success();
+    env.admin_call("set_min_reserve", args!(dec!("40"))).expect_commit_success();
+
+    // a withdrawal breaching the reserve is rejected
+    let receipt = env.admin_call("withdraw", args!(dec!("61")));
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("requested 61 but only 60 available above the reserve of 40")
+    });
+    env.admin_call("withdraw", args!(dec!("10"))).expect_commit_success();
+
+    // withdrawing everything leaves the reserve
+    let balance_before = env.xrd_balance();
+    env.admin_call("withdrawal_all", args!()).expect_commit_success();
+    assert_eq!(env.xrd_balance(), balance_before + dec!("50"));
+    assert_eq!(env.read::<Decimal>("vault_balance", args!()), dec!("40"));
+
+    let receipt = env.admin_call("withdrawal_all", args!());
+    receipt.expect_commit_success();
+    assert_eq!(env.read::<Decimal>("vault_balance", args!()), dec!("40"));
+}
+
+#[test]
+fn test_set_nickname() {
+    let mut env = TestEnv::new();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+    assert_eq!(env.read::<String>("get_nickname", args!(ticket_id.clone())), "");
+
+    env.set_nickname(&ticket_id, "Lucky Seven").expect_commit_success();
+    assert_eq!(env.read::<String>("get_nickname", args!(ticket_id.clone())), "Lucky Seven");
+
+    let too_long = "x".repeat(MAX_NICKNAME_LENGTH + 1);
+    let receipt = env.set_nickname(&ticket_id, &too_long);
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("The nickname can be at most 32 characters long")
+    });
+    assert_eq!(env.read::<String>("get_nickname", args!(ticket_id)), "Lucky Seven");
+}
+
+#[test]
+fn test_can_pay_prize() {
+    let mut env = TestEnv::new();
+    assert!(!env.read::<bool>("can_pay_prize", args!()));
+
+    // ticket sales go to the main wallet, the prize vault stays underfunded
+    env.buy_tickets(10, dec!("10")).expect_commit_success();
+    assert!(!env.read::<bool>("can_pay_prize", args!()));
+
+    env.fund_prize_vault(RADIX_TOKEN, dec!("4")).expect_commit_success();
+    assert!(!env.read::<bool>("can_pay_prize", args!()));
+    env.fund_prize_vault(RADIX_TOKEN, dec!("1")).expect_commit_success();
+    assert!(env.read::<bool>("can_pay_prize", args!()));
+}
+
+#[test]
+fn test_instantiate_with_existing_ticket_resource() {
+    let mut env = TestEnv::new();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let old_ticket_id = env.last_ticket_id();
+    env.admin_call("withdraw_admin_badge", args!()).expect_commit_success();
+
+    // a second component, e.g. a redeployed package, takes over the internal admin badge
+    let receipt = env.instantiate_with_resource(env.admin_badge, 1);
+    receipt.expect_commit_success();
+    let commit = receipt.expect_commit();
+    assert!(commit.entity_changes.new_resource_addresses.is_empty());
+    env.component = commit.entity_changes.new_component_addresses[0];
+
+    // the old ticket plays on the new component, new tickets continue the ids
+    env.play_round(&old_ticket_id).expect_commit_success();
+    let receipt = env.buy_ticket(dec!("1"));
+    receipt.expect_commit_success();
+    let minted: Vec<TicketMinted> = env.events(&receipt, "TicketMinted");
+    assert_eq!(minted[0].ticket_id, env.ticket_id(2));
+    assert_eq!(env.read::<u64>("total_tickets_minted", args!()), 2);
+    assert_eq!(env.test_runner.account_balance(env.account, env.ticket), Some(dec!("2")));
+}
+
+#[test]
+fn test_redeem_inherited_winning_ticket() {
+    let mut env = TestEnv::new();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let winner = env.last_ticket_id();
+    env.play_to_win(&winner);
+    env.admin_call("withdraw_admin_badge", args!()).expect_commit_success();
+
+    let receipt = env.instantiate_with_resource(env.admin_badge, 1);
+    receipt.expect_commit_success();
+    env.component = receipt.expect_commit().entity_changes.new_component_addresses[0];
+    assert_eq!(env.read::<GameSnapshot>("snapshot", args!()).tickets_at_max, 0);
+
+    // the new component never counted the ticket on the win level, redeeming it doesn't underflow
+    env.fund_prize_vault(RADIX_TOKEN, dec!("10")).expect_commit_success();
+    let receipt = env.redeem_prize(&winner);
+    receipt.expect_commit_success();
+    let redeemed: Vec<PrizeRedeemed> = env.events(&receipt, "PrizeRedeemed");
+    assert_eq!(redeemed[0].gross, dec!("5"));
+    assert_eq!(env.read::<GameSnapshot>("snapshot", args!()).tickets_at_max, 0);
+}
+
+#[test]
+fn test_withdraw_to() {
+    let mut env = TestEnv::new();
+    env.buy_tickets(5, dec!("5")).expect_commit_success();
+    env.deposit(dec!("20"), dec!("20")).expect_commit_success();
+    let vault_balance: Decimal = env.read("vault_balance", args!());
+
+    let balance_before = env.xrd_balance();
+    let receipt = env.admin_call("withdraw_to", args!());
+    receipt.expect_commit_success();
+    let withdrawn: Vec<FundsWithdrawn> = env.events(&receipt, "FundsWithdrawn");
+    assert_eq!(withdrawn.len(), 1);
+    assert_eq!(withdrawn[0].amount, vault_balance);
+    assert_eq!(env.xrd_balance(), balance_before + vault_balance);
+    assert_eq!(env.read::<Decimal>("vault_balance", args!()), dec!("0"));
+}
+
+#[test]
+fn test_sweep_to() {
+    let mut env = TestEnv::new();
+    env.deposit(dec!("100"), dec!("100")).expect_commit_success();
+
+    let balance_before = env.xrd_balance();
+    let receipt = env.admin_call("sweep_to", args!(dec!("30")));
+    assert_vault_changed(&env, &receipt, "70", "sweep");
+    let sweeps: Vec<SweepPerformed> = env.events(&receipt, "SweepPerformed");
+    assert_eq!(sweeps.len(), 1);
+    assert_eq!(sweeps[0].amount, dec!("30"));
+    assert_eq!(env.xrd_balance(), balance_before + dec!("30"));
+
+    // a single admin badge can't sweep
+    let receipt = env.admin_call_with_badges(dec!("1"), "sweep_to", args!(dec!("10")));
+    receipt.expect_specific_failure(|e| format!("{:?}", e).contains("Unauthorized"));
+}
+
+#[test]
+fn test_sweep_to_keeps_reserve() {
+    let mut env = TestEnv::new();
+    env.deposit(dec!("100"), dec!("100")).expect_commit_success();
+    env.admin_call("set_min_reserve", args!(dec!("40"))).expect_commit_success();
+
+    let receipt = env.admin_call("sweep_to", args!(dec!("61")));
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("requested 61 but only 60 available above the reserve of 40")
+    });
+    env.admin_call("sweep_to", args!(dec!("60"))).expect_commit_success();
+    assert_eq!(env.read::<Decimal>("vault_balance", args!()), dec!("40"));
+}
+
+#[test]
+fn test_bust_consolation() {
+    let mut env = TestEnv::new();
+    env.deposit(dec!("10"), dec!("10")).expect_commit_success();
+    env.admin_call("set_bust_consolation", args!(dec!("0.5"))).expect_commit_success();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+
+    let receipt = env.bust_from_level_one(&ticket_id);
+    let busted: Vec<Busted> = env.events(&receipt, "Busted");
+    assert_eq!(busted.len(), 1);
+    assert_eq!(busted[0].ticket_id, ticket_id);
+    assert_eq!(busted[0].consolation, dec!("0.5"));
+    assert_vault_changed(&env, &receipt, "10.4", "consolation");
+    let consolation: Decimal = env.read("get_consolation", args!(ticket_id.clone()));
+    assert_eq!(consolation, dec!("0.5"));
+
+    // the ticket stays on level 0 and can be reinitialized
+    let (level, _): (i8, String) = env.read("get_ticket_status", args!(ticket_id.clone()));
+    assert_eq!(level, 0);
+    let balance_before = env.xrd_balance();
+    env.claim_consolation(&ticket_id).expect_commit_success();
+    assert_eq!(env.xrd_balance(), balance_before + dec!("0.5"));
+    let receipt = env.claim_consolation(&ticket_id);
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("No consolation to claim for this ticket")
+    });
+    env.reinit_ticket(&ticket_id, dec!("1")).expect_commit_success();
+}
+
+#[test]
+fn test_bust_consolation_without_funds() {
+    let mut env = TestEnv::new();
+    env.admin_call("set_bust_consolation", args!(dec!("5"))).expect_commit_success();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+
+    // the main wallet only holds the 0.9 XRD of the ticket sale, the rest went to the jackpot
+    let receipt = env.bust_from_level_one(&ticket_id);
+    let busted: Vec<Busted> = env.events(&receipt, "Busted");
+    assert_eq!(busted.len(), 1);
+    assert_eq!(busted[0].consolation, dec!("0"));
+    assert!(env.events::<VaultChanged>(&receipt, "VaultChanged").is_empty());
+    let consolation: Decimal = env.read("get_consolation", args!(ticket_id.clone()));
+    assert_eq!(consolation, dec!("0"));
+    assert_eq!(env.read::<Decimal>("vault_balance", args!()), dec!("0.9"));
+}
+
+#[test]
+fn test_get_config() {
+    let mut env = TestEnv::with_config(Config {
+        win_level: 20,
+        start_level: 8,
+        prize_amount: dec!("7"),
+        ticket_price: dec!("2"),
+        ..Default::default()
+    });
+    let config: (Decimal, Decimal, i8, i8) = env.read("get_config", args!());
+    assert_eq!(config, (dec!("2"), dec!("7"), 8, 20));
+}
+
+#[test]
+fn test_streak_bonus() {
+    let mut env = TestEnv::new();
+    env.admin_call("set_streak_bonus_threshold", args!(3u8)).expect_commit_success();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+
+    // follow the streak round by round until a bonus was granted and a loss broke a streak
+    let (mut streak, mut bonus_seen, mut reset_seen) = (0u8, false, false);
+    for _ in 0..300 {
+        env.set_ticket_level(&ticket_id, 10).expect_commit_success();
+        let receipt = env.play_round(&ticket_id);
+        receipt.expect_commit_success();
+        let round: Vec<RoundPlayed> = env.events(&receipt, "RoundPlayed");
+        let diff = round[0].player_die - round[0].house_die;
+        let mut expected_level = 10 + diff;
+        if diff > 0 {
+            streak += 1;
+            if streak == 3 {
+                expected_level += 1;
+                streak = 0;
+                bonus_seen = true;
+            }
+        } else if diff < 0 {
+            reset_seen |= streak > 0;
+            streak = 0;
+        }
+        assert_eq!(round[0].new_level, expected_level);
+        assert_eq!(env.read::<u8>("get_win_streak", args!(ticket_id.clone())), streak);
+        if bonus_seen && reset_seen {
+            return;
+        }
+    }
+    panic!("No streak bonus and streak reset seen in 300 rounds");
+}
+
+#[test]
+fn test_streak_bonus_within_max_delta() {
+    let mut env = TestEnv::new();
+    env.admin_call("set_streak_bonus_threshold", args!(1u8)).expect_commit_success();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+
+    // every win earns the bonus, it is logged as part of the level change
+    env.admin_call("set_rng_override", args!(Some((4i8, 5i8)))).expect_commit_success();
+    env.play_round(&ticket_id).expect_commit_success();
+    let (level, last_throw): (i8, String) = env.read("get_ticket_status", args!(ticket_id.clone()));
+    assert_eq!((level, last_throw.as_str()), (12, "House 4, Player 5, New Lvl 12(+2)"));
+
+    // the bonus can't push a round past max_delta, +3 and the bonus are clamped to +2
+    env.admin_call("set_max_delta", args!(2i8)).expect_commit_success();
+    env.admin_call("set_rng_override", args!(Some((2i8, 5i8)))).expect_commit_success();
+    env.set_ticket_level(&ticket_id, 10).expect_commit_success();
+    env.play_round(&ticket_id).expect_commit_success();
+    let (level, last_throw): (i8, String) = env.read("get_ticket_status", args!(ticket_id.clone()));
+    assert_eq!((level, last_throw.as_str()), (12, "House 2, Player 5, New Lvl 12(+2)"));
+}
+
+#[test]
+fn test_streak_bonus_disabled_by_default() {
+    let mut env = TestEnv::new();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+
+    for _ in 0..20 {
+        env.set_ticket_level(&ticket_id, 10).expect_commit_success();
+        let receipt = env.play_round(&ticket_id);
+        let round: Vec<RoundPlayed> = env.events(&receipt, "RoundPlayed");
+        assert_eq!(round[0].new_level, 10 + round[0].player_die - round[0].house_die);
+    }
+}
+
+#[test]
+fn test_play_team_round() {
+    let mut env = TestEnv::new();
+    env.buy_tickets(2, dec!("2")).expect_commit_success();
+    let (first, second) = (env.ticket_id(1), env.ticket_id(2));
+    env.set_ticket_level(&second, 12).expect_commit_success();
+
+    let receipt = env.play_team_round(BTreeSet::from([first.clone(), second.clone()]));
+    receipt.expect_commit_success();
+    let rounds: Vec<RoundPlayed> = env.events(&receipt, "RoundPlayed");
+    assert_eq!(rounds.len(), 2);
+    assert_eq!(rounds[0].house_die, rounds[1].house_die);
+    assert_eq!(rounds[0].player_die, rounds[1].player_die);
+    let delta = rounds[0].new_level - rounds[0].old_level;
+    assert_eq!(rounds[1].new_level - rounds[1].old_level, delta);
+    for (ticket_id, start) in [(first, 10), (second, 12)] {
+        let (level, _): (i8, String) = env.read("get_ticket_status", args!(ticket_id));
+        assert_eq!(level, start + delta);
+    }
+}
+
+#[test]
+fn test_play_team_round_too_large() {
+    let mut env = TestEnv::new();
+    let team_size = MAX_TEAM_SIZE as u32 + 1;
+    env.buy_tickets(team_size, Decimal::from(team_size)).expect_commit_success();
+    let team: BTreeSet<NonFungibleLocalId> =
+        (1..=team_size as u64).map(|n| env.ticket_id(n)).collect();
+
+    let receipt = env.play_team_round(team);
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("A team has between 1 and 5 tickets, got 6")
+    });
+}
+
+#[test]
+fn test_level_math_past_i8_range_is_clamped() {
+    // a staked d20 round moves up to 57 levels, 126 + 57 doesn't fit in an i8
+    let mut env = TestEnv::with_config(Config {
+        win_level: 127,
+        start_level: 120,
+        dice_faces: MAX_DICE_FACES,
+        ..Default::default()
+    });
+    env.deposit(dec!("100"), dec!("100")).expect_commit_success();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+
+    let (mut overflow_seen, mut below_zero_seen) = (false, false);
+    for round in 0..100 {
+        let start: i8 = if round % 2 == 0 { 126 } else { 1 };
+        env.set_ticket_level(&ticket_id, start).expect_commit_success();
+        let receipt = env.play_round_staked(&ticket_id, dec!("2"), 3);
+        receipt.expect_commit_success();
+        let played: Vec<RoundPlayed> = env.events(&receipt, "RoundPlayed");
+        let diff = 3 * (played[0].player_die as i32 - played[0].house_die as i32);
+        let unclamped = start as i32 + diff;
+        assert_eq!(played[0].new_level as i32, unclamped.clamp(0, 127));
+        overflow_seen |= unclamped > i8::MAX as i32;
+        below_zero_seen |= unclamped < 0;
+        if overflow_seen && below_zero_seen {
+            return;
+        }
+    }
+    panic!("No round overflowed an i8 and dropped below 0 in 100 rounds");
+}
+
+#[test]
+fn test_rng_override() {
+    let mut env = TestEnv::new();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+
+    assert_eq!(env.read::<Option<(i8, i8)>>("rng_override", args!()), None);
+    let receipt = env.admin_call("set_rng_override", args!(Some((6i8, 1i8))));
+    receipt.expect_commit_success();
+    let set: Vec<RngOverrideSet> = env.events(&receipt, "RngOverrideSet");
+    assert_eq!(set[0].rng_override, Some((6, 1)));
+    assert_eq!(env.read::<Option<(i8, i8)>>("rng_override", args!()), Some((6, 1)));
+    let receipt = env.play_round(&ticket_id);
+    receipt.expect_commit_success();
+    let played: Vec<RoundPlayed> = env.events(&receipt, "RoundPlayed");
+    assert_eq!((played[0].house_die, played[0].player_die), (6, 1));
+    assert_eq!((played[0].old_level, played[0].new_level), (10, 5));
+
+    let receipt = env.admin_call("set_rng_override", args!(Some((7i8, 1i8))));
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("The forced dice must be between 1 and 6")
+    });
+
+    // only an admin can force the dice
+    let manifest = ManifestBuilder::new()
+        .call_method(env.component, "set_rng_override", args!(None::<(i8, i8)>))
+        .build();
+    env.execute(manifest)
+        .expect_specific_failure(|e| format!("{:?}", e).contains("Unauthorized"));
+
+    // without the override the dice are rolled again
+    let receipt = env.admin_call("set_rng_override", args!(None::<(i8, i8)>));
+    receipt.expect_commit_success();
+    let set: Vec<RngOverrideSet> = env.events(&receipt, "RngOverrideSet");
+    assert_eq!(set[0].rng_override, None);
+    assert_eq!(env.read::<Option<(i8, i8)>>("rng_override", args!()), None);
+    env.set_ticket_level(&ticket_id, 10).expect_commit_success();
+    let receipt = env.play_round(&ticket_id);
+    let played: Vec<RoundPlayed> = env.events(&receipt, "RoundPlayed");
+    assert_eq!(played[0].new_level, 10 + played[0].player_die - played[0].house_die);
+
+    // a production game can't force the dice, not even with both admin badges
+    let mut env = TestEnv::with_config(Config {
+        allow_rng_override: false,
+        ..Default::default()
+    });
+    let receipt = env.admin_call("set_rng_override", args!(Some((1i8, 6i8))));
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("Forcing the dice is disabled for this game")
+    });
+}
+
+#[test]
+fn test_snapshot() {
+    let mut env = TestEnv::with_config(Config {
+        dice_mode: DICE_MODE_PLAYER_FAVORED,
+        max_tickets: 10,
+        ..Default::default()
+    });
+    env.deposit(dec!("50"), dec!("50")).expect_commit_success();
+    env.fund_prize_vault(RADIX_TOKEN, dec!("20")).expect_commit_success();
+    env.buy_tickets(3, dec!("3")).expect_commit_success();
+    let ticket_id = env.ticket_id(2);
+    env.play_to_win(&ticket_id);
+    env.admin_call("set_paused", args!(true)).expect_commit_success();
+
+    let snapshot: GameSnapshot = env.read("snapshot", args!());
+    assert_eq!(
+        snapshot,
+        GameSnapshot {
+            vault_balance: dec!("52.7"),
+            prize_vault_balance: dec!("20"),
+            jackpot_balance: dec!("0.3"),
+            tickets_minted: 3,
+            tickets_at_max: 1,
+            paused: true,
+            ticket_price: dec!("1"),
+            prize_amount: dec!("5"),
+            start_level: 10,
+            win_level: 25,
+            dice_mode: DICE_MODE_PLAYER_FAVORED,
+            dice_faces: 6,
+            single_die_mode: false,
+            max_tickets: 10,
+        }
+    );
+}
+
+#[test]
+fn test_buy_and_play() {
+    let mut env = TestEnv::new();
+    env.admin_call("set_rng_override", args!(Some((2i8, 5i8)))).expect_commit_success();
+
+    let balance_before = env.xrd_balance();
+    let manifest = ManifestBuilder::new()
+        .withdraw_from_account_by_amount(env.account, dec!("2"), RADIX_TOKEN)
+        .take_from_worktop(RADIX_TOKEN, |builder, bucket_id| {
+            builder.call_method(env.component, "buy_and_play", args!(bucket_id))
+        })
+        .call_method(
+            env.account,
+            "deposit_batch",
+            args!(ManifestExpression::EntireWorktop),
+        )
+        .build();
+    let receipt = env.execute(manifest);
+    receipt.expect_commit_success();
+    env.minted += 1;
+
+    let ticket_id = env.last_ticket_id();
+    let played: Vec<RoundPlayed> = env.events(&receipt, "RoundPlayed");
+    assert_eq!(played[0].ticket_id, ticket_id);
+    assert_eq!((played[0].old_level, played[0].new_level), (10, 13));
+    let (status_level, _): (i8, String) = env.read("get_ticket_status", args!(ticket_id));
+    assert_eq!(status_level, 13);
+    assert_eq!(env.xrd_balance(), balance_before - dec!("1"));
+    assert_eq!(env.test_runner.account_balance(env.account, env.ticket), Some(dec!("1")));
+}
+
+#[test]
+fn test_custom_buyin_resource() {
+    let mut env = TestEnv::with_config(Config {
+        reward_token_buyin: true,
+        ..Default::default()
+    });
+    let reward_token = env.reward_token;
+
+    let receipt = env.buy_ticket(dec!("1"));
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("can only be done with the accepted buy-in resource")
+    });
+
+    env.buy_ticket_with(reward_token, dec!("3")).expect_commit_success();
+    let balance = env.test_runner.account_balance(env.account, reward_token);
+    assert_eq!(balance, Some(dec!("999")));
+    assert_eq!(env.read::<Decimal>("vault_balance", args!()), dec!("0.9"));
+    assert_eq!(env.read::<Decimal>("jackpot_balance", args!()), dec!("0.1"));
+
+    // the house is funded with the buy-in resource as well
+    env.fund_house(reward_token, dec!("10")).expect_commit_success();
+    assert_eq!(env.read::<Decimal>("vault_balance", args!()), dec!("10.9"));
+    let receipt = env.fund_house(RADIX_TOKEN, dec!("10"));
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("can only be funded with the accepted buy-in resource")
+    });
+}
+
+#[test]
+fn test_burn_ticket_of_winning_level() {
+    let mut env = TestEnv::new();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+    env.play_to_win(&ticket_id);
+    assert_eq!(env.read::<Decimal>("outstanding_prize_liability", args!()), dec!("5"));
+
+    let receipt = env.burn_ticket(&ticket_id);
+    receipt.expect_commit_success();
+    let burned: Vec<TicketBurned> = env.events(&receipt, "TicketBurned");
+    assert_eq!(burned.len(), 1);
+    assert_eq!(burned[0].ticket_id, ticket_id);
+    assert_eq!(burned[0].level, 25);
+    // a burned winning ticket can't claim the prize anymore
+    assert_eq!(env.read::<Decimal>("outstanding_prize_liability", args!()), dec!("0"));
+}
+
+#[test]
+fn test_burn_ticket_wrong_resource() {
+    let mut env = TestEnv::new();
+    let other_nft = env.test_runner.create_non_fungible_resource(env.account);
+
+    let receipt = env.burn_ticket_of(other_nft, &NonFungibleLocalId::integer(1));
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("does not contain the correct Ticket address")
+    });
+}
+
+#[test]
+fn test_separate_minter_and_updater_badges() {
+    let (mut test_runner, _, account, _, receipt) = instantiate(Config::default());
+    let resources = receipt.expect_commit().entity_changes.new_resource_addresses.clone();
+    assert_eq!(resources.len(), 4);
+    for (resource, name) in [
+        (resources[1], "Ticket Minter Badge for RaDiceX"),
+        (resources[2], "Ticket Updater Badge for RaDiceX"),
+    ] {
+        let address = GlobalAddress::Resource(resource);
+        assert_eq!(test_runner.get_metadata(address, "name"), Some(name.to_string()));
+        // the badges stay in the component
+        assert_eq!(test_runner.account_balance(account, resource), None);
+    }
+
+    // minting, updating and burning each run with their own badge
+    let mut env = TestEnv::new();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+    env.play_round(&ticket_id).expect_commit_success();
+    env.set_ticket_level(&ticket_id, 7).expect_commit_success();
+    env.burn_ticket(&ticket_id).expect_commit_success();
+}
+
+#[test]
+fn test_minter_and_updater_badges_are_limited() {
+    let mut env = TestEnv::new();
+    let (minter_badge, updater_badge) = (env.minter_badge, env.updater_badge);
+    let receipt = env.admin_call_with_badges(dec!("1"), "withdraw_ticket_badges", args!());
+    receipt.expect_specific_failure(|e| format!("{:?}", e).contains("Unauthorized"));
+    env.admin_call("withdraw_ticket_badges", args!()).expect_commit_success();
+    let receipt = env.buy_ticket(dec!("1"));
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("The ticket badges are withdrawn")
+    });
+
+    // with the badges back the game mints tickets again
+    let manifest = ManifestBuilder::new()
+        .create_proof_from_account(env.account, env.admin_badge)
+        .withdraw_from_account_by_amount(env.account, dec!("1"), minter_badge)
+        .withdraw_from_account_by_amount(env.account, dec!("1"), updater_badge)
+        .take_from_worktop(minter_badge, |builder, minter_bucket| {
+            builder.take_from_worktop(updater_badge, |builder, updater_bucket| {
+                builder.call_method(
+                    env.component,
+                    "deposit_ticket_badges",
+                    args!(minter_bucket, updater_bucket),
+                )
+            })
+        })
+        .build();
+    env.execute(manifest).expect_commit_success();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    env.admin_call("withdraw_ticket_badges", args!()).expect_commit_success();
+
+    // a component running on the minter badge alone mints tickets, but can't update or burn them
+    let receipt = env.instantiate_with_resource(minter_badge, 1);
+    receipt.expect_commit_success();
+    env.component = receipt.expect_commit().entity_changes.new_component_addresses[0];
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+    for receipt in [env.play_round(&ticket_id), env.burn_ticket(&ticket_id)] {
+        receipt.expect_specific_failure(|e| format!("{:?}", e).contains("Unauthorized"));
+    }
+
+    // a component running on the updater badge alone can't mint tickets
+    let receipt = env.instantiate_with_resource(updater_badge, 2);
+    receipt.expect_commit_success();
+    env.component = receipt.expect_commit().entity_changes.new_component_addresses[0];
+    let receipt = env.buy_ticket(dec!("1"));
+    receipt.expect_specific_failure(|e| format!("{:?}", e).contains("Unauthorized"));
+}
+
+#[test]
+fn test_fractional_price_rounded_to_divisibility() {
+    // a price of 1.005 can't be paid in a token with 2 decimals, 1.01 is charged
+    let mut env = TestEnv::with_config(Config {
+        ticket_price: dec!("1.005"),
+        reward_token_buyin: true,
+        reward_token_divisibility: 2,
+        ..Default::default()
+    });
+    let reward_token = env.reward_token;
+
+    env.buy_ticket_with(reward_token, dec!("5")).expect_commit_success();
+    let balance = env.test_runner.account_balance(env.account, reward_token).unwrap();
+    let vault = env.read::<Decimal>("vault_balance", args!());
+    let jackpot = env.read::<Decimal>("jackpot_balance", args!());
+    // the jackpot share of 0.101 is rounded down, the dust stays in the main wallet
+    assert_eq!(jackpot, dec!("0.1"));
+    assert_eq!(vault, dec!("0.91"));
+    assert_eq!(balance + vault + jackpot, dec!("1000"));
+}
+
+#[test]
+fn test_refund_and_consolation_rounded_to_divisibility() {
+    // amounts of 0.257 can't be paid in a token with 2 decimals, 0.25 is set aside and refunded
+    let mut env = TestEnv::with_config(Config {
+        reward_token_buyin: true,
+        reward_token_divisibility: 2,
+        ..Default::default()
+    });
+    let reward_token = env.reward_token;
+    env.fund_house(reward_token, dec!("10")).expect_commit_success();
+    env.admin_call("set_bust_consolation", args!(dec!("0.257"))).expect_commit_success();
+    env.admin_call("set_dead_ticket_refund", args!(dec!("0.257"))).expect_commit_success();
+    env.buy_ticket_with(reward_token, dec!("1")).expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+
+    let receipt = env.bust_from_level_one(&ticket_id);
+    let busted: Vec<Busted> = env.events(&receipt, "Busted");
+    assert_eq!(busted[0].consolation, dec!("0.25"));
+
+    let balance_before = env.test_runner.account_balance(env.account, reward_token).unwrap();
+    env.refund_dead_ticket(&ticket_id).expect_commit_success();
+    let balance = env.test_runner.account_balance(env.account, reward_token).unwrap();
+    assert_eq!(balance, balance_before + dec!("0.25"));
+}
+
+#[test]
+fn test_recent_global_throws() {
+    let mut env = TestEnv::new();
+    env.buy_tickets(2, dec!("2")).expect_commit_success();
+    let (first, second) = (env.ticket_id(1), env.ticket_id(2));
+    env.admin_call("set_rng_override", args!(Some((2i8, 5i8)))).expect_commit_success();
+    assert!(env.read::<Vec<String>>("recent_global_throws", args!()).is_empty());
+
+    for ticket_id in [&first, &second, &first] {
+        env.play_round(ticket_id).expect_commit_success();
+    }
+    let feed: Vec<String> = env.read("recent_global_throws", args!());
+    assert_eq!(
+        feed,
+        vec![
+            "Ticket #1#: House 2, Player 5, New Lvl 13(+3)".to_string(),
+            "Ticket #2#: House 2, Player 5, New Lvl 13(+3)".to_string(),
+            "Ticket #1#: House 2, Player 5, New Lvl 16(+3)".to_string(),
+        ]
+    );
+
+    // tied dice keep the level, so the feed fills up with the rounds of one ticket
+    env.admin_call("set_rng_override", args!(Some((3i8, 3i8)))).expect_commit_success();
+    env.play_rounds(&second, 25).expect_commit_success();
+    let feed: Vec<String> = env.read("recent_global_throws", args!());
+    assert_eq!(feed.len(), RECENT_GLOBAL_THROWS);
+    assert!(feed.iter().all(|throw| throw == "Ticket #2#: House 3, Player 3, New Lvl 13(+0)"));
+}
+
+#[test]
+fn test_referral_rewards() {
+    let mut env = TestEnv::new();
+    env.admin_call("set_referral_reward", args!(dec!("0.2"))).expect_commit_success();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let referrer = env.last_ticket_id();
+
+    for _ in 0..2 {
+        env.buy_ticket_referred(&referrer, dec!("1")).expect_commit_success();
+    }
+    let accrued: Decimal = env.read("get_referral_balance", args!(referrer.clone()));
+    assert_eq!(accrued, dec!("0.4"));
+    // the buy-ins include the referral rewards
+    assert_eq!(env.read::<Decimal>("total_buyins", args!()), dec!("3"));
+    // the reward comes out of the ticket price, the jackpot share of the rest goes to the jackpot
+    assert_eq!(env.read::<Decimal>("vault_balance", args!()), dec!("0.9") + dec!("1.44"));
+
+    let balance_before = env.xrd_balance();
+    env.claim_referral(&referrer).expect_commit_success();
+    assert_eq!(env.xrd_balance(), balance_before + dec!("0.4"));
+    let receipt = env.claim_referral(&referrer);
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("No referral rewards to claim for this ticket")
+    });
+}
+
+#[test]
+fn test_referral_needs_existing_ticket() {
+    let mut env = TestEnv::new();
+    env.admin_call("set_referral_reward", args!(dec!("0.2"))).expect_commit_success();
+
+    let receipt = env.buy_ticket_referred(&NonFungibleLocalId::integer(7), dec!("1"));
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("The referrer ticket #7# does not exist")
+    });
+
+    let receipt = env.admin_call("set_referral_reward", args!(dec!("1.5")));
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("The referral reward must be between 0 and the ticket price 1")
+    });
+}
+
+#[test]
+fn test_no_referral_by_ticket_of_same_transaction() {
+    let mut env = TestEnv::new();
+    env.admin_call("set_referral_reward", args!(dec!("0.2"))).expect_commit_success();
+
+    // buy a ticket and refer a second sale with it in one transaction
+    let referrer = env.ticket_id(env.minted + 1);
+    let manifest = ManifestBuilder::new()
+        .withdraw_from_account_by_amount(env.account, dec!("2"), RADIX_TOKEN)
+        .take_from_worktop_by_amount(dec!("1"), RADIX_TOKEN, |builder, bucket_id| {
+            builder.call_method(env.component, "buy_ticket", args!(bucket_id))
+        })
+        .take_from_worktop_by_amount(dec!("1"), RADIX_TOKEN, |builder, bucket_id| {
+            builder.call_method(
+                env.component,
+                "buy_ticket_referred",
+                args!(bucket_id, referrer.clone()),
+            )
+        })
+        .call_method(
+            env.account,
+            "deposit_batch",
+            args!(ManifestExpression::EntireWorktop),
+        )
+        .build();
+    env.execute(manifest).expect_specific_failure(|e| {
+        format!("{:?}", e).contains("can not be bought in the same transaction")
+    });
+
+    // the same ticket bought in an earlier transaction can refer a sale
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    assert_eq!(env.last_ticket_id(), referrer);
+    env.buy_ticket_referred(&referrer, dec!("1")).expect_commit_success();
+    assert_eq!(env.read::<Decimal>("get_referral_balance", args!(referrer)), dec!("0.2"));
+}
+
+#[test]
+fn test_roll_die_modulo() {
+    // 7 is rejected by a d6 with rejection sampling, its next 3 bits (0) give a 1
+    assert_eq!(roll_die(|| 7, 6, false), 1);
+    assert_eq!(roll_die_modulo(|| 7, 6, false), 2);
+    // a loaded die takes the extra value 6 (after the modulo) as its highest face
+    assert_eq!(roll_die_modulo(|| 6, 6, true), 6);
+    assert_eq!(roll_die_modulo(|| 5, 6, true), 6);
+    assert_eq!(roll_die_modulo(|| 7, 6, true), 1);
+
+    let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+    for faces in MIN_DICE_FACES..=MAX_DICE_FACES {
+        for loaded in [false, true] {
+            let die = roll_die_modulo(|| rng.next(), faces, loaded);
+            assert!((1..=faces as i8).contains(&die));
+        }
+    }
+}
+
+#[test]
+fn test_set_rng_strategy() {
+    let mut env = TestEnv::new();
+    assert_eq!(env.read::<u8>("rng_strategy", args!()), RNG_STRATEGY_REJECTION);
+
+    env.admin_call("set_rng_strategy", args!(RNG_STRATEGY_MODULO)).expect_commit_success();
+    assert_eq!(env.read::<u8>("rng_strategy", args!()), RNG_STRATEGY_MODULO);
+    for _ in 0..20 {
+        let die: i8 = env.read("roll_dice_preview", args!());
+        assert!((1..=6).contains(&die));
+    }
+
+    let receipt = env.admin_call("set_rng_strategy", args!(2u8));
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("The rng strategy must be 0 (rejection sampling) or 1 (modulo)")
+    });
+}
+
+#[test]
+fn test_finished_tickets_not_playable_with_custom_win_level() {
+    let mut env = TestEnv::with_config(Config {
+        win_level: 15,
+        ..Default::default()
+    });
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let (maxed, busted) = (env.ticket_id(1), env.ticket_id(2));
+    env.play_to_win(&maxed);
+    env.set_ticket_level(&busted, 0).expect_commit_success();
+
+    let receipt = env.play_round(&maxed);
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("Ticket Level = 15 is the win level, Ticket not playable")
+    });
+    let receipt = env.play_round(&busted);
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("Ticket Level = 0, Ticket not playable")
+    });
+
+    // a ticket below the custom win level still plays
+    env.set_ticket_level(&maxed, 14).expect_commit_success();
+    env.play_round(&maxed).expect_commit_success();
+}
+
+#[test]
+fn test_set_prize_amount() {
+    // a new ticket starts at level 10, so a win level of 11 is reached quickly
+    let mut env = TestEnv::with_config(Config {
+        win_level: 11,
+        ..Default::default()
+    });
+    env.fund_prize_vault(RADIX_TOKEN, dec!("100")).expect_commit_success();
+    let ticket_id = env.win_ticket(dec!("1"));
+
+    // the ticket was maxed under the old amount, the new amount is paid
+    let receipt = env.admin_call("set_prize_amount", args!(dec!("3")));
+    receipt.expect_commit_success();
+    let events: Vec<PrizeAmountChanged> = env.events(&receipt, "PrizeAmountChanged");
+    assert_eq!(events.len(), 1);
+    assert_eq!((events[0].old, events[0].new), (dec!("5"), dec!("3")));
+
+    let jackpot: Decimal = env.read("jackpot_balance", args!());
+    let balance_before = env.xrd_balance();
+    env.redeem_prize(&ticket_id).expect_commit_success();
+    assert_eq!(env.xrd_balance(), balance_before + dec!("3") + jackpot);
+
+    let receipt = env.admin_call("set_prize_amount", args!(dec!("-1")));
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("The prize amount can not be negative")
+    });
+}
+
+#[test]
+fn test_dice_drawn_recomputed_from_raw() {
+    let mut env = TestEnv::new();
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+    // a d6 ticket on level 12 is still playable after two rounds
+    env.set_ticket_level(&ticket_id, 12).expect_commit_success();
+
+    for rng_strategy in [RNG_STRATEGY_REJECTION, RNG_STRATEGY_MODULO] {
+        env.admin_call("set_rng_strategy", args!(rng_strategy)).expect_commit_success();
+        let receipt = env.play_round(&ticket_id);
+        receipt.expect_commit_success();
+
+        let drawn: Vec<DiceDrawn> = env.events(&receipt, "DiceDrawn");
+        assert_eq!(drawn.len(), 1);
+        // at least one random number per die
+        assert!(drawn[0].raws.len() >= 2);
+        let dice = (drawn[0].house_die, drawn[0].player_die);
+        let recomputed = throw_from_raws(&drawn[0].raws, 6, DICE_MODE_FAIR, false, rng_strategy);
+        assert_eq!(recomputed, dice);
+
+        let round = env.events::<RoundPlayed>(&receipt, "RoundPlayed").remove(0);
+        assert_eq!((round.house_die, round.player_die), dice);
+    }
+
+    // forced dice are not drawn from a random number
+    env.admin_call("set_rng_override", args!(Some((1i8, 2i8)))).expect_commit_success();
+    let receipt = env.play_round(&ticket_id);
+    receipt.expect_commit_success();
+    assert!(env.events::<DiceDrawn>(&receipt, "DiceDrawn").is_empty());
+}
+
+#[test]
+fn test_play_instant_payout_brackets() {
+    let mut env = TestEnv::with_config(Config {
+        payout_table: Some(vec![(5, dec!("6")), (3, dec!("3")), (1, dec!("1.5"))]),
+        ..Default::default()
+    });
+    env.fund_house(RADIX_TOKEN, dec!("100")).expect_commit_success();
+    assert_eq!(env.read::<Vec<(i8, Decimal)>>("payout_table", args!()).len(), 3);
+
+    // (house, player) dice for each bracket, outcomes missing from the table pay nothing
+    let brackets = [
+        ((1i8, 6i8), dec!("12")),
+        ((2, 5), dec!("6")),
+        ((3, 4), dec!("3")),
+        ((4, 2), dec!("0")),
+    ];
+    for (dice, payout) in brackets {
+        env.admin_call("set_rng_override", args!(Some(dice))).expect_commit_success();
+        let balance_before = env.xrd_balance();
+        let receipt = env.play_instant(dec!("2"));
+        receipt.expect_commit_success();
+        assert_eq!(env.xrd_balance(), balance_before - dec!("2") + payout);
+
+        let played: Vec<InstantPlayed> = env.events(&receipt, "InstantPlayed");
+        assert_eq!((played[0].house_die, played[0].player_die), dice);
+        assert_eq!((played[0].stake, played[0].payout), (dec!("2"), payout));
+    }
+}
+
+#[test]
+fn test_play_instant_needs_payout_table_and_funds() {
+    let mut env = TestEnv::new();
+    let receipt = env.play_instant(dec!("1"));
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("This is not a fixed-odds game, there is no payout table")
+    });
+
+    // the empty main wallet can't cover a 6x payout
+    let mut env = TestEnv::with_config(Config {
+        payout_table: Some(vec![(5, dec!("6"))]),
+        ..Default::default()
+    });
+    let receipt = env.play_instant(dec!("1"));
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("Not enough funds in the vault to cover the highest payout")
+    });
+
+    // the minimum reserve doesn't back payouts, 6x a 1 XRD stake needs 5 XRD above the reserve
+    env.fund_house(RADIX_TOKEN, dec!("10")).expect_commit_success();
+    env.admin_call("set_min_reserve", args!(dec!("6"))).expect_commit_success();
+    let receipt = env.play_instant(dec!("1"));
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("Not enough funds in the vault to cover the highest payout")
+    });
+    env.admin_call("set_min_reserve", args!(dec!("5"))).expect_commit_success();
+    env.play_instant(dec!("1")).expect_commit_success();
+
+    let (_, _, _, _, receipt) = instantiate(Config {
+        payout_table: Some(vec![(6, dec!("6"))]),
+        ..Default::default()
+    });
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("Payout outcome 6 must be between -5 and 5")
+    });
+
+    let (_, _, _, _, receipt) = instantiate(Config {
+        payout_table: Some(vec![(0, dec!("1"))]),
+        ..Default::default()
+    });
+    receipt.expect_specific_failure(|e| {
+        format!("{:?}", e).contains("Payout outcome 0 can not be listed, ties are thrown again")
+    });
+}
+
+#[test]
+fn test_fixed_odds_game_has_no_tickets() {
+    let mut env = TestEnv::with_config(Config {
+        payout_table: Some(vec![(5, dec!("6"))]),
+        ..Default::default()
+    });
+    env.fund_house(RADIX_TOKEN, dec!("100")).expect_commit_success();
+
+    let no_tickets = "This is a fixed-odds game without tickets, play with play_instant";
+    for receipt in [
+        env.buy_ticket(dec!("1")),
+        env.buy_tickets(2, dec!("2")),
+        env.admin_call("admin_ticket", args!()),
+        env.admin_call("mint_promo_ticket", args!(20i8)),
+    ] {
+        receipt.expect_specific_failure(|e| format!("{:?}", e).contains(no_tickets));
+    }
+    assert_eq!(env.read::<u64>("total_tickets_minted", args!()), 0);
+}
+
+#[test]
+fn test_internal_operations_without_admin_badge() {
+    let mut env = TestEnv::with_config(Config {
+        win_level: 11,
+        ..Default::default()
+    });
+    env.fund_prize_vault(RADIX_TOKEN, dec!("100")).expect_commit_success();
+    let winner = env.win_ticket(dec!("1"));
+    env.buy_ticket(dec!("1")).expect_commit_success();
+    let ticket_id = env.last_ticket_id();
+    env.admin_call("withdraw_admin_badge", args!()).expect_commit_success();
+
+    let disabled = "The internal admin badge is withdrawn, internal operations are disabled";
+    for receipt in [
+        env.redeem_prize(&winner),
+        env.set_nickname(&ticket_id, "Lucky"),
+        env.burn_ticket(&ticket_id),
+        env.set_ticket_level(&ticket_id, 5),
+    ] {
+        receipt.expect_specific_failure(|e| format!("{:?}", e).contains(disabled));
+    }
+}
+
+#[test]
+fn test_theoretical_rtp() {
+    // chances of a round add up to 1
+    let distribution = delta_distribution(6, DICE_MODE_FAIR, false);
+    let total = distribution.iter().fold(dec!("0"), |sum, (_, chance)| sum + *chance);
+    assert!((total - dec!("1")).abs() < dec!("0.000000001"));
+
+    // a fair single die is the gambler's ruin, a ticket on level 10 of 25 wins with a 10/25 chance
+    let distribution = delta_distribution(6, DICE_MODE_FAIR, true);
+    let win_chance = win_probability(&distribution, 10, 25, 0);
+    assert!((win_chance - dec!("0.4")).abs() < dec!("0.000000001"));
+
+    // precomputed off-chain with exact fractions: 2.03286, 3.74735 and 0.60008 for a 5 XRD prize
+    let expected = [
+        (DICE_MODE_FAIR, dec!("2.0329")),
+        (DICE_MODE_PLAYER_FAVORED, dec!("3.7474")),
+        (DICE_MODE_HOUSE_FAVORED, dec!("0.6001")),
+    ];
+    for (dice_mode, rtp) in expected {
+        let mut env = TestEnv::with_config(Config {
+            dice_mode,
+            ..Default::default()
+        });
+        let theoretical_rtp: Decimal = env.read("theoretical_rtp", args!());
+        assert!((theoretical_rtp - rtp).abs() <= dec!("0.0001"), "{} != {}", theoretical_rtp, rtp);
+    }
+
+    // the redeem fee lowers the return
+    let mut env = TestEnv::with_config(Config {
+        single_die_mode: true,
+        ..Default::default()
+    });
+    assert_eq!(env.read::<Decimal>("theoretical_rtp", args!()), dec!("2"));
+    env.admin_call("set_redeem_fee_fraction", args!(dec!("0.1"))).expect_commit_success();
+    assert_eq!(env.read::<Decimal>("theoretical_rtp", args!()), dec!("1.8"));
+}